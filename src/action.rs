@@ -2,6 +2,13 @@ use crate::components::Component;
 
 pub enum Action {
     None,
-    ChangeComponent(Box<dyn Component + Send>),
+    /// Draws again, for changes that did not come from input.
+    Render,
+    /// Opens a component over the current one, which is kept as it is until this one is popped.
+    Push(Box<dyn Component + Send>),
+    /// Closes the current component and returns to the one below it.
+    Pop,
+    /// Swaps the current component for another one.
+    Replace(Box<dyn Component + Send>),
     Quit,
-}
\ No newline at end of file
+}
@@ -0,0 +1,32 @@
+pub mod mcts;
+pub mod minimax;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, ValueEnum, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Difficulty {
+    Random,
+    Easy,
+    Medium,
+    Perfect,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 4] = [Difficulty::Random, Difficulty::Easy, Difficulty::Medium, Difficulty::Perfect];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Random => "Random",
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Perfect => "Perfect",
+        }
+    }
+
+    /// Moves `delta` steps along the difficulty scale, stopping at either end.
+    pub fn step(self, delta: isize) -> Difficulty {
+        let index = Self::ALL.iter().position(|difficulty| *difficulty == self).unwrap();
+        Self::ALL[index.saturating_add_signed(delta).min(Self::ALL.len() - 1)]
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::engine::{Outcome, Square, SuperBoard, SuperMove};
+
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// How long a search may run; it stops at whichever limit is reached first.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Budget {
+    pub time: Duration,
+    pub iterations: Option<usize>,
+}
+
+impl Budget {
+    pub fn time(time: Duration) -> Self {
+        Budget { time, iterations: None }
+    }
+}
+
+struct Node {
+    mv: Option<SuperMove>,
+    /// The player who made `mv`, whose point of view `wins` is counted from.
+    player: Square,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<SuperMove>,
+    visits: u32,
+    wins: f64,
+}
+
+/// Runs Monte Carlo Tree Search from `board` and returns the most visited move. Setting `stop`
+/// ends the search early, as if the budget ran out.
+pub fn search(board: &SuperBoard, budget: Budget, stop: &AtomicBool) -> Option<SuperMove> {
+    let mut rng = rand::thread_rng();
+    let mut untried = board.legal_moves();
+    untried.shuffle(&mut rng);
+    let mut nodes = vec![Node { mv: None, player: board.turn().opponent(), parent: None, children: Vec::new(), untried, visits: 0, wins: 0.0 }];
+
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < budget.time && budget.iterations.is_none_or(|limit| iterations < limit) && !stop.load(Ordering::Relaxed) {
+        iterations += 1;
+        let mut state = board.clone();
+
+        let mut current = 0;
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            current = select_child(&nodes, current);
+            state.play(nodes[current].mv.unwrap()).unwrap();
+        }
+
+        if let Some(mv) = nodes[current].untried.pop() {
+            let player = state.turn();
+            state.play(mv).unwrap();
+            let mut untried = state.legal_moves();
+            untried.shuffle(&mut rng);
+            nodes.push(Node { mv: Some(mv), player, parent: Some(current), children: Vec::new(), untried, visits: 0, wins: 0.0 });
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+        }
+
+        while state.outcome() == Outcome::InProgress {
+            let moves = state.legal_moves();
+            state.play(moves[rng.gen_range(0..moves.len())]).unwrap();
+        }
+
+        let winner = state.outcome().winner();
+        let mut node = Some(current);
+        while let Some(index) = node {
+            nodes[index].visits += 1;
+            nodes[index].wins += if winner == nodes[index].player {
+                1.0
+            } else if winner == Square::Draw {
+                0.5
+            } else {
+                0.0
+            };
+            node = nodes[index].parent;
+        }
+    }
+
+    nodes[0].children.iter()
+        .max_by_key(|&&child| nodes[child].visits)
+        .and_then(|&child| nodes[child].mv)
+        .or_else(|| board.legal_moves().first().copied())
+}
+
+fn select_child(nodes: &[Node], parent: usize) -> usize {
+    let log_visits = (nodes[parent].visits as f64).ln();
+    *nodes[parent].children.iter().max_by(|&&a, &&b| {
+        uct(&nodes[a], log_visits).total_cmp(&uct(&nodes[b], log_visits))
+    }).unwrap()
+}
+
+fn uct(node: &Node, log_parent_visits: f64) -> f64 {
+    let visits = node.visits as f64;
+    node.wins / visits + EXPLORATION * (log_parent_visits / visits).sqrt()
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use crate::ai::Difficulty;
+use crate::engine::{Board, Outcome, Position, Square};
+
+const WIN: i64 = 1 << 60;
+const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// Maps a square onto its image under a board symmetry, given the board width and height.
+type Transform = fn(usize, usize, usize, usize) -> Position;
+
+/// A square to play and the mark to place there.
+type Move = (Position, Square);
+
+/// Picks a move for the player to move, or `None` if the game is over.
+pub fn best_move(board: &Board, difficulty: Difficulty) -> Option<Move> {
+    let mut moves = candidates(board);
+    moves.shuffle(&mut rand::thread_rng());
+    let small = board.width() * board.height() <= 9;
+    let depth = match difficulty {
+        Difficulty::Random => return moves.first().copied(),
+        Difficulty::Easy => Some(2),
+        Difficulty::Medium => Some(if small { 4 } else { 2 }),
+        Difficulty::Perfect => if small { None } else { Some(3) },
+    };
+    Search { depth, table: HashMap::new() }.root(board, moves)
+}
+
+#[derive(Copy, Clone)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone)]
+struct Entry {
+    remaining: usize,
+    value: i64,
+    bound: Bound,
+}
+
+/// Negamax with alpha-beta pruning; `depth` of `None` searches every line to the end.
+struct Search {
+    depth: Option<usize>,
+    table: HashMap<Vec<u8>, Entry>,
+}
+
+impl Search {
+    fn root(&mut self, board: &Board, moves: Vec<Move>) -> Option<Move> {
+        let remaining = self.depth.unwrap_or(usize::MAX);
+        let mut best = None;
+        let mut alpha = -WIN * 2;
+        for (mv, mark) in moves {
+            let mut child = board.clone();
+            if child.play_mark(mv, mark).is_err() {
+                continue;
+            }
+            let value = -self.negamax(&child, remaining - 1, 1, -WIN * 2, -alpha);
+            if best.is_none() || value > alpha {
+                alpha = value;
+                best = Some((mv, mark));
+            }
+        }
+        best
+    }
+
+    fn negamax(&mut self, board: &Board, remaining: usize, ply: i64, mut alpha: i64, mut beta: i64) -> i64 {
+        match board.outcome() {
+            // Usually the player who just moved won, in misère they completed a line and lost.
+            Outcome::Win(winner, _) if winner == board.turn() => return WIN - ply,
+            Outcome::Win(..) => return -(WIN - ply),
+            Outcome::Draw => return 0,
+            Outcome::InProgress => {}
+        }
+        if remaining == 0 {
+            return evaluate(board);
+        }
+
+        let key = canonical_key(board);
+        let original_alpha = alpha;
+        if let Some(entry) = self.table.get(&key) {
+            if entry.remaining >= remaining {
+                match entry.bound {
+                    Bound::Exact => return entry.value,
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return entry.value;
+                }
+            }
+        }
+
+        let mut value = -WIN * 2;
+        for (mv, mark) in candidates(board) {
+            let mut child = board.clone();
+            if child.play_mark(mv, mark).is_err() {
+                continue;
+            }
+            value = value.max(-self.negamax(&child, remaining - 1, ply + 1, -beta, -alpha));
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if value <= original_alpha {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, Entry { remaining, value, bound });
+        value
+    }
+}
+
+/// Moves worth searching: on larger boards only squares next to an existing mark. With gravity
+/// there are few enough moves to search them all. In wild games every square is tried with both marks.
+fn candidates(board: &Board) -> Vec<Move> {
+    let marks = if board.rules().wild { vec![Square::X, Square::Circle] } else { vec![board.turn()] };
+    squares(board).into_iter().flat_map(|mv| marks.iter().map(move |mark| (mv, *mark))).collect()
+}
+
+fn squares(board: &Board) -> Vec<Position> {
+    let moves = board.legal_moves();
+    if board.width() * board.height() <= 16 || board.rules().gravity {
+        return moves;
+    }
+    let near: Vec<Position> = moves.iter().copied().filter(|&(x, y)| {
+        (-1..=1).any(|dx: i64| (-1..=1).any(|dy: i64| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            nx >= 0 && ny >= 0 && matches!(board.get((nx as usize, ny as usize)), Square::X | Square::Circle)
+        }))
+    }).collect();
+    if near.is_empty() {
+        vec![(board.width() / 2, board.height() / 2)]
+    } else {
+        near
+    }
+}
+
+/// Scores every open window of `win_length` squares from the view of the player to move, lines
+/// being a threat rather than a chance in misère. Wild games are not scored, any line being open
+/// to both players.
+fn evaluate(board: &Board) -> i64 {
+    if board.rules().wild {
+        return 0;
+    }
+    let me = board.turn();
+    let length = board.win_length() as i64;
+    let mut score = 0;
+    for x in 0..board.width() as i64 {
+        for y in 0..board.height() as i64 {
+            for (dx, dy) in DIRECTIONS {
+                let (end_x, end_y) = (x + dx * (length - 1), y + dy * (length - 1));
+                if end_x < 0 || end_y < 0 || end_x >= board.width() as i64 || end_y >= board.height() as i64 {
+                    continue;
+                }
+                let (mut mine, mut theirs) = (0, 0);
+                for i in 0..length {
+                    let square = board.get(((x + dx * i) as usize, (y + dy * i) as usize));
+                    if square == me {
+                        mine += 1;
+                    } else if square != Square::None {
+                        theirs += 1;
+                    }
+                }
+                if theirs == 0 && mine > 0 {
+                    score += 1 << (2 * mine);
+                } else if mine == 0 && theirs > 0 {
+                    score -= 1 << (2 * theirs);
+                }
+            }
+        }
+    }
+    if board.rules().misere { -score } else { score }
+}
+
+/// Encodes the position so that boards equal up to rotation or reflection share a key.
+fn canonical_key(board: &Board) -> Vec<u8> {
+    let (width, height) = (board.width(), board.height());
+    let mut transforms: Vec<Transform> = vec![
+        |x, y, _, _| (x, y),
+        |x, y, w, _| (w - 1 - x, y),
+        |x, y, _, h| (x, h - 1 - y),
+        |x, y, w, h| (w - 1 - x, h - 1 - y),
+    ];
+    if board.rules().gravity {
+        // Gravity pulls towards the bottom, so only the mirror image plays the same.
+        transforms.truncate(2);
+    } else if width == height {
+        transforms.extend_from_slice(&[
+            |x, y, _, _| (y, x),
+            |x, y, w, _| (w - 1 - y, x),
+            |x, y, _, h| (y, h - 1 - x),
+            |x, y, w, h| (w - 1 - y, h - 1 - x),
+        ]);
+    }
+    transforms.iter().map(|transform| {
+        let mut key = Vec::with_capacity(width * height + 1);
+        key.push(board.turn() as u8);
+        for y in 0..height {
+            for x in 0..width {
+                key.push(board.get(transform(x, y, width, height)) as u8);
+            }
+        }
+        key
+    }).min().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Rules;
+
+    #[test]
+    fn misere_computer_avoids_completing_a_line() {
+        let mut board = Board::new(3, 3, 3);
+        board.set_rules(Rules { misere: true, ..Rules::default() });
+        for mv in [(0, 0), (2, 2), (1, 0), (0, 2)] {
+            board.play(mv).unwrap();
+        }
+        for _ in 0..10 {
+            assert_ne!(best_move(&board, Difficulty::Perfect), Some(((2, 0), Square::X)));
+        }
+    }
+
+    #[test]
+    fn wild_computer_completes_a_line_of_the_other_mark() {
+        let mut board = Board::new(3, 3, 3);
+        board.set_rules(Rules { wild: true, ..Rules::default() });
+        for (mv, mark) in [((0, 0), Square::Circle), ((2, 2), Square::X), ((1, 0), Square::Circle), ((0, 1), Square::X)] {
+            board.play_mark(mv, mark).unwrap();
+        }
+        assert_eq!(best_move(&board, Difficulty::Easy), Some(((2, 0), Square::Circle)));
+    }
+}
@@ -3,41 +3,55 @@ use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
 use ratatui::backend::CrosstermBackend;
 use ratatui::crossterm::{ExecutableCommand};
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::{Terminal};
 use crate::action::Action;
+use crate::cli::Cli;
+use crate::config;
 use crate::components::Component;
 use crate::components::main_menu::MainMenu;
+use crate::event;
+use crate::event::Event;
 
 type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 pub struct App {
     tui: Tui,
-    component: Box<dyn Component + Send>,
+    /// Open components, the one on top receives events.
+    components: Vec<Box<dyn Component + Send>>,
 }
 
 impl App {
-    pub async fn start() -> Result<()> {
-        stdout().execute(EnterAlternateScreen)?;
+    pub async fn start(cli: Cli) -> Result<()> {
+        config::set(config::load().wrap_err("Failed to load the config")?);
+        let component = cli.component().unwrap_or_else(|error| error.exit());
+        stdout().execute(EnterAlternateScreen)?.execute(EnableMouseCapture)?;
         enable_raw_mode()?;
         Self::set_panic_hook();
-        let mut app = App { tui: Terminal::new(CrosstermBackend::new(stdout()))?, component: Box::new(MainMenu::new()) };
+        let mut app = App { tui: Terminal::new(CrosstermBackend::new(stdout()))?, components: vec![component] };
         app.run().await
     }
 
     async fn run(&mut self) -> Result<()> {
+        let mut events = event::listen().expect("the app listens for events once");
+        let mut changed = true;
         loop {
-            let update_action = self.component.update().await.wrap_err("Failed to update component")?;
-            if !self.handle_action(update_action) {
-                break;
+            if changed {
+                self.draw()?;
             }
-
-            self.tui.draw(|frame| {
-                self.component.render(frame, frame.size());
-            })?;
-
-            let event_action = self.component.handle_event().await.wrap_err("Failed to handle events")?;
-            if !self.handle_action(event_action) {
+            let Some(event) = events.recv().await else {
+                break;
+            };
+            let (action, input) = match event {
+                Event::Input(input) => (self.component().handle_event(input).await.wrap_err("Failed to handle events")?, true),
+                Event::InputError(error) => return Err(error).wrap_err("Failed to read events"),
+                Event::Tick => (self.component().update().await.wrap_err("Failed to update component")?, false),
+                Event::Action(action) => (action, false),
+            };
+            // Input is drawn right away, anything else only if it says it changed something.
+            changed = input || !matches!(action, Action::None);
+            if !self.handle_action(action) {
                 break;
             }
         }
@@ -45,29 +59,58 @@ impl App {
         Ok(())
     }
 
+    fn draw(&mut self) -> Result<()> {
+        // Overlays are drawn over the components below them, down to the first one that fills the screen.
+        let components = &mut self.components;
+        let base = components.iter().rposition(|component| !component.is_overlay()).unwrap_or(0);
+        self.tui.draw(|frame| {
+            for component in &mut components[base..] {
+                component.render(frame, frame.size());
+            }
+        })?;
+        Ok(())
+    }
+
     fn handle_action(&mut self, action: Action) -> bool {
         match action {
             Action::Quit => false,
-            Action::ChangeComponent(new_component) => {
-                self.component = new_component;
+            Action::Push(component) => {
+                self.component().pause();
+                self.components.push(component);
+                true
+            }
+            Action::Pop => {
+                self.components.pop();
+                // A game started from the command line has nothing below it to return to.
+                if self.components.is_empty() {
+                    self.components.push(Box::new(MainMenu::new()));
+                }
                 true
             }
-            _ => true
+            Action::Replace(component) => {
+                self.components.pop();
+                self.components.push(component);
+                true
+            }
+            Action::None | Action::Render => true
         }
+    }
 
+    fn component(&mut self) -> &mut Box<dyn Component + Send> {
+        self.components.last_mut().expect("there is always a component open")
     }
 
     fn set_panic_hook() {
         let hook = std::panic::take_hook();
         std::panic::set_hook(Box::new(move |panic_info| {
-            let _ = Self::stop();
+            Self::stop();
             hook(panic_info);
             eprintln!("Please provide errors to https://github.com/notTamion/github-tui/issues");
         }));
     }
 
     fn stop() {
-        stdout().execute(LeaveAlternateScreen).unwrap();
+        stdout().execute(DisableMouseCapture).unwrap().execute(LeaveAlternateScreen).unwrap();
         disable_raw_mode().unwrap();
     }
 }
@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+use crate::ai::Difficulty;
+use crate::ai::mcts::Budget;
+use crate::components::Component;
+use crate::components::load_game;
+use crate::components::local_game::LocalGame;
+use crate::components::main_menu::MainMenu;
+use crate::components::online_lobby::OnlineLobby;
+use crate::components::super_local_game::SuperLocalGame;
+use crate::config;
+use crate::net::protocol::{Settings, MAX_SIZE};
+use crate::save;
+
+/// Tic-tac-toe in the terminal. Without options it starts at the main menu.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// Start a local game of this kind
+    #[arg(long, value_enum)]
+    mode: Option<Mode>,
+    /// Width and height of the board, or of every board in a super game, from 3 to 32
+    #[arg(long)]
+    size: Option<usize>,
+    /// Marks in a row needed to win a normal game
+    #[arg(long)]
+    win_length: Option<usize>,
+    /// Play against the computer
+    #[arg(long, value_enum, value_name = "DIFFICULTY")]
+    vs_ai: Option<Difficulty>,
+    /// Have the super game computer stop after this many iterations of its search, even with
+    /// think time left
+    #[arg(long, value_name = "COUNT", requires = "vs_ai")]
+    iterations: Option<usize>,
+    /// Host an online game and wait for an opponent on this address
+    #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["vs_ai", "join", "load"])]
+    host: Option<String>,
+    /// Join the online game hosted at this address
+    #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["mode", "size", "win_length", "vs_ai", "load"])]
+    join: Option<String>,
+    /// Continue a saved game
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["mode", "size", "win_length", "vs_ai"])]
+    load: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, PartialEq, Eq, Copy, Clone, Debug)]
+enum Mode {
+    Normal,
+    Super,
+}
+
+impl Cli {
+    /// The component the options start at, or why they do not describe a game.
+    pub fn component(self) -> Result<Box<dyn Component + Send>, clap::Error> {
+        if let Some(path) = self.load {
+            return save::load(&path)
+                .and_then(|saved| load_game::resume(path.clone(), saved))
+                .map_err(|error| Self::error(ErrorKind::Io, format!("cannot load {}: {error}", path.display())));
+        }
+        if let Some(address) = self.join {
+            return Ok(Box::new(OnlineLobby::join_address(address)));
+        }
+        if self.host.is_none() && self.mode.is_none() && self.size.is_none() && self.win_length.is_none() && self.vs_ai.is_none() {
+            return Ok(Box::new(MainMenu::new()));
+        }
+
+        let defaults = config::get().defaults;
+        if let Some(size) = self.size.filter(|size| !(3..=MAX_SIZE).contains(size)) {
+            return Err(Self::error(ErrorKind::ValueValidation, format!("--size must be between 3 and {MAX_SIZE}, got {size}")));
+        }
+        let mode = self.mode.unwrap_or(match defaults.mode {
+            config::Mode::Normal => Mode::Normal,
+            config::Mode::Super => Mode::Super,
+        });
+        let settings = match mode {
+            Mode::Normal => {
+                let (width, height) = self.size.map_or((defaults.width, defaults.height), |size| (size, size));
+                let win_length = self.win_length.unwrap_or(defaults.win_length.min(width.max(height)));
+                if !(3..=width.max(height)).contains(&win_length) {
+                    return Err(Self::error(ErrorKind::ValueValidation, format!("--win-length must be between 3 and the size {}, got {win_length}", width.max(height))));
+                }
+                Settings::Normal { width, height, win_length }
+            }
+            Mode::Super if self.win_length.is_some() => {
+                return Err(Self::error(ErrorKind::ArgumentConflict, "--win-length only applies to normal games, super games are won with a full row of boards"));
+            }
+            Mode::Super => Settings::Super { size: self.size.unwrap_or(defaults.super_size) },
+        };
+
+        if let Some(address) = self.host {
+            if !settings.is_valid() {
+                return Err(Self::error(ErrorKind::ValueValidation, format!("the board is too large for an online game, use --size {MAX_SIZE} or less")));
+            }
+            return Ok(Box::new(OnlineLobby::host_address(address, settings)));
+        }
+        if self.iterations == Some(0) {
+            return Err(Self::error(ErrorKind::ValueValidation, "--iterations must be at least 1"));
+        }
+        Ok(match settings {
+            Settings::Normal { .. } if self.iterations.is_some() => {
+                return Err(Self::error(ErrorKind::ArgumentConflict, "--iterations only applies to super games, the normal computer searches by difficulty"));
+            }
+            Settings::Normal { width, height, win_length } => Box::new(LocalGame::with_settings(width, height, win_length, self.vs_ai)),
+            Settings::Super { size } => {
+                let budget = match self.vs_ai {
+                    None => None,
+                    Some(Difficulty::Random) => {
+                        return Err(Self::error(ErrorKind::InvalidValue, "the super computer has no random difficulty, use easy, medium or perfect"));
+                    }
+                    Some(Difficulty::Easy) => Some(Budget::time(Duration::from_millis(500))),
+                    Some(Difficulty::Medium) => Some(Budget::time(Duration::from_secs(1))),
+                    Some(Difficulty::Perfect) => Some(Budget::time(Duration::from_secs(5))),
+                };
+                let budget = budget.map(|budget| Budget { iterations: self.iterations, ..budget });
+                Box::new(SuperLocalGame::with_settings(size, budget))
+            }
+        })
+    }
+
+    fn error(kind: ErrorKind, message: impl std::fmt::Display) -> clap::Error {
+        Self::command().error(kind, message)
+    }
+}
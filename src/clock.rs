@@ -0,0 +1,208 @@
+//! Chess-style clocks for local games.
+
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::engine::{Square, MARKS};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TimeControl {
+    /// `time` for the whole game, with `increment` added after every move.
+    Total { time: Duration, increment: Duration },
+    /// `time` for every move, whatever is left over is not kept.
+    PerMove(Duration),
+}
+
+impl TimeControl {
+    /// The time controls to choose from, `None` playing without a clock.
+    const ALL: [Option<TimeControl>; 7] = [
+        None,
+        Some(TimeControl::Total { time: Duration::from_secs(60), increment: Duration::ZERO }),
+        Some(TimeControl::Total { time: Duration::from_secs(180), increment: Duration::ZERO }),
+        Some(TimeControl::Total { time: Duration::from_secs(30), increment: Duration::from_secs(2) }),
+        Some(TimeControl::Total { time: Duration::from_secs(60), increment: Duration::from_secs(5) }),
+        Some(TimeControl::PerMove(Duration::from_secs(5))),
+        Some(TimeControl::PerMove(Duration::from_secs(10))),
+    ];
+
+    pub fn name(&self) -> String {
+        match *self {
+            TimeControl::Total { time, increment } if increment.is_zero() => short_name(time),
+            TimeControl::Total { time, increment } => format!("{}+{}", short_name(time), short_name(increment)),
+            TimeControl::PerMove(time) => format!("{}/move", short_name(time)),
+        }
+    }
+
+    /// Moves `delta` steps through the time controls, stopping at either end.
+    pub fn step(control: Option<TimeControl>, delta: isize) -> Option<TimeControl> {
+        let index = Self::ALL.iter().position(|other| *other == control).unwrap_or(0);
+        Self::ALL[index.saturating_add_signed(delta).min(Self::ALL.len() - 1)]
+    }
+
+    fn start(self) -> Duration {
+        match self {
+            TimeControl::Total { time, .. } | TimeControl::PerMove(time) => time,
+        }
+    }
+}
+
+fn short_name(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 && secs.is_multiple_of(60) { format!("{}m", secs / 60) } else { format!("{secs}s") }
+}
+
+/// What happens to a player whose time runs out.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum OnTimeout {
+    #[default]
+    Lose,
+    /// A random move is played for them and the game goes on.
+    RandomMove,
+}
+
+impl OnTimeout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OnTimeout::Lose => "Lose",
+            OnTimeout::RandomMove => "Random",
+        }
+    }
+
+    pub fn toggle(self) -> OnTimeout {
+        match self {
+            OnTimeout::Lose => OnTimeout::RandomMove,
+            OnTimeout::RandomMove => OnTimeout::Lose,
+        }
+    }
+}
+
+pub struct Clock {
+    control: TimeControl,
+    /// The time left for each player, in the order of [`MARKS`].
+    left: [Duration; MARKS.len()],
+    /// Whose time is running and since when it was last charged.
+    running: Option<(Square, Instant)>,
+    moves: usize,
+    shown: String,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Clock { control, left: [control.start(); MARKS.len()], running: None, moves: 0, shown: String::new() }
+    }
+
+    /// A stopped clock for a game resumed from a save, each player having the time in `left`
+    /// in the order of [`MARKS`].
+    pub fn resume(control: TimeControl, left: &[Duration]) -> Self {
+        let mut clock = Clock::new(control);
+        for (time, saved) in clock.left.iter_mut().zip(left) {
+            *time = *saved;
+        }
+        clock
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Gives every player their full time again, for a new game.
+    pub fn reset(&mut self) {
+        *self = Clock::new(self.control);
+    }
+
+    /// Keeps the clock in step with a game where `moves` moves were played and `turn` is to move.
+    /// Whoever was to move since the last call is charged the time and gets the increment if
+    /// they have moved since, and unless `paused` the clock runs for `turn`.
+    pub fn follow(&mut self, turn: Square, moves: usize, paused: bool) {
+        let now = Instant::now();
+        let mover = self.running.take().map(|(mark, since)| {
+            let left = &mut self.left[mark.index()];
+            *left = left.saturating_sub(now - since);
+            mark
+        });
+        if let Some(mover) = mover.filter(|_| moves > self.moves) {
+            let left = &mut self.left[mover.index()];
+            match self.control {
+                TimeControl::Total { increment, .. } => *left += increment,
+                TimeControl::PerMove(time) => *left = time,
+            }
+        }
+        self.moves = moves;
+        if !paused {
+            self.running = Some((turn, now));
+        }
+    }
+
+    pub fn left(&self, mark: Square) -> Duration {
+        let left = self.left[mark.index()];
+        match self.running {
+            Some((running, since)) if running == mark => left.saturating_sub(since.elapsed()),
+            _ => left,
+        }
+    }
+
+    /// The player whose time is running and has run out.
+    pub fn expired(&self) -> Option<Square> {
+        self.running.map(|(mark, _)| mark).filter(|mark| self.left(*mark).is_zero())
+    }
+
+    /// The time left for `mark` as shown next to their name, with tenths in the last ten seconds.
+    /// Whole seconds are rounded up so that a full minute shows as 1:00 until it starts running.
+    pub fn show(&self, mark: Square) -> String {
+        let left = self.left(mark);
+        if left <= Duration::from_secs(10) {
+            format!("{}.{}", left.as_secs(), left.subsec_millis() / 100)
+        } else {
+            let secs = left.as_millis().div_ceil(1000);
+            format!("{}:{:02}", secs / 60, secs % 60)
+        }
+    }
+
+    /// Whether the times shown changed since the last call.
+    pub fn changed(&mut self) -> bool {
+        let shown: Vec<String> = MARKS.iter().map(|mark| self.show(*mark)).collect();
+        let shown = shown.join(" ");
+        shown != std::mem::replace(&mut self.shown, shown.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn increment_is_added_after_a_move() {
+        let mut clock = Clock::new(TimeControl::Total { time: Duration::from_secs(30), increment: Duration::from_secs(2) });
+        clock.follow(Square::X, 0, false);
+        clock.follow(Square::Circle, 1, false);
+        assert!(clock.left(Square::X) > Duration::from_secs(31));
+        assert!(clock.left(Square::X) <= Duration::from_secs(32));
+        assert_eq!(clock.show(Square::X), "0:32");
+    }
+
+    #[test]
+    fn per_move_time_starts_over_after_a_move() {
+        let mut clock = Clock::new(TimeControl::PerMove(Duration::from_secs(5)));
+        clock.left[0] = Duration::from_secs(1);
+        clock.follow(Square::X, 0, false);
+        clock.follow(Square::Circle, 1, false);
+        assert_eq!(clock.left(Square::X), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn only_a_running_clock_expires() {
+        let mut clock = Clock::new(TimeControl::PerMove(Duration::ZERO));
+        assert_eq!(clock.expired(), None);
+        clock.follow(Square::Circle, 1, false);
+        assert_eq!(clock.expired(), Some(Square::Circle));
+        clock.follow(Square::Circle, 1, true);
+        assert_eq!(clock.expired(), None);
+    }
+
+    #[test]
+    fn stepping_through_the_time_controls() {
+        let control = TimeControl::step(None, 3).unwrap();
+        assert_eq!(control.name(), "30s+2s");
+        assert_eq!(TimeControl::step(Some(control), 10).unwrap().name(), "10s/move");
+        assert_eq!(TimeControl::step(Some(control), -5), None);
+    }
+}
@@ -1,29 +1,39 @@
-use std::time::Duration;
 use color_eyre::Result;
 use async_trait::async_trait;
-use ratatui::crossterm::event;
-use ratatui::crossterm::event::{Event, KeyEvent};
+use ratatui::crossterm::event::{Event, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::Frame;
 use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
 use crate::action::Action;
 
 pub mod main_menu;
-mod local_game;
+pub mod local_game;
 mod game_selection;
-mod super_local_game;
+pub mod super_local_game;
 mod game;
 mod super_game;
+mod cube_game;
+pub mod online_lobby;
+mod online_game;
+mod lan_browser;
+pub mod load_game;
+mod replay;
+mod statistics;
+mod settings;
+mod help;
+mod confirm;
+mod session;
+#[cfg(test)]
+mod harness;
 
 #[async_trait]
 pub trait Component {
 
-    async fn handle_event(&mut self) -> Result<Action> {
-        if event::poll(Duration::from_millis(16))? == false {
-            return Ok(Action::None)
-        }
-        match event::read()? {
+    async fn handle_event(&mut self, event: Event) -> Result<Action> {
+        match event {
             Event::Key(event) => self.handle_key_event(event).await,
             Event::Paste(pasted_string)  => self.handle_paste(pasted_string),
+            Event::Mouse(event) => self.handle_mouse_event(event).await,
             _ => Ok(Action::None)
         }
     }
@@ -32,13 +42,44 @@ pub trait Component {
         Ok(Action::None)
     }
 
+    async fn handle_mouse_event(&mut self, _mouse_event: MouseEvent) -> Result<Action> {
+        Ok(Action::None)
+    }
+
     fn handle_paste(&mut self, _pasted_string: String) -> Result<Action> {
         Ok(Action::None)
     }
 
+    /// Called on every tick. Returns `Action::Render` when something changed that should be drawn.
     async fn update(&mut self) -> Result<Action> {
         Ok(Action::None)
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect);
-}
\ No newline at end of file
+
+    /// Called when another component opens on top of this one, which stops getting updates until
+    /// it is on top again, e.g. to stop a clock from charging a player for that time.
+    fn pause(&mut self) {}
+
+    /// Whether the component only covers part of the screen, leaving the one below it visible.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// Selects the item of a list of `len` items last drawn into `area` that the mouse is over.
+/// Returns whether it was clicked.
+pub fn point_at_item(mouse_event: &MouseEvent, area: Rect, list_state: &mut ListState, len: usize) -> bool {
+    if !matches!(mouse_event.kind, MouseEventKind::Moved | MouseEventKind::Down(MouseButton::Left)) {
+        return false;
+    }
+    if mouse_event.column < area.left() || mouse_event.column >= area.right() || mouse_event.row < area.top() || mouse_event.row >= area.bottom() {
+        return false;
+    }
+    let index = list_state.offset() + (mouse_event.row - area.top()) as usize;
+    if index >= len {
+        return false;
+    }
+    list_state.select(Some(index));
+    mouse_event.kind == MouseEventKind::Down(MouseButton::Left)
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use crate::action::Action;
+use crate::components::Component;
+use crate::config;
+use crate::keymap::KeyAction;
+
+/// Asks a yes or no question over the current screen. Yes answers with `action`, no closes the dialog.
+pub struct Confirm {
+    question: String,
+    action: Option<Action>,
+}
+
+#[async_trait]
+impl Component for Confirm {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+        match config::key_action(&key_event) {
+            // Pressing quit again confirms quitting.
+            Some(KeyAction::Confirm | KeyAction::Quit) => Ok(self.action.take().unwrap_or(Action::Pop)),
+            Some(KeyAction::Back) => Ok(Action::Pop),
+            _ => Ok(Action::None),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let hint = "Enter to confirm, Esc to cancel";
+        let width = self.question.chars().count().max(hint.len()) as u16 + 4;
+        let area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(width),
+                Constraint::Fill(1)]
+            ).split(Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(5),
+                Constraint::Fill(1)]
+            ).split(area)[1])[1];
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(format!("{}\n\n{hint}", self.question)).centered().block(Block::bordered()), area);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+impl Confirm {
+    pub fn new(question: impl Into<String>, action: Action) -> Self {
+        Confirm { question: question.into(), action: Some(action) }
+    }
+}
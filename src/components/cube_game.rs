@@ -0,0 +1,325 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::prelude::Text;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Paragraph;
+use crate::action::Action;
+use crate::components::Component;
+use crate::components::game::Game;
+use crate::components::help::Help;
+use crate::components::session;
+use crate::components::session::{Choice, MenuItem, Session};
+use crate::config;
+use crate::engine::{CubeBoard, CubeMove, MoveError, Square};
+use crate::keymap::KeyAction;
+use crate::stats::{GameResult, Variant};
+
+const MENU: [MenuItem; 7] = [MenuItem::Resume, MenuItem::Undo, MenuItem::Redo, MenuItem::Rematch, MenuItem::Restart, MenuItem::Menu, MenuItem::Quit];
+const MAX_SIZE: usize = 6;
+
+/// Qubic played locally, the layers of the cube drawn side by side with the bottom one on the left.
+pub struct CubeGame {
+    board: CubeBoard,
+    /// A view of every layer, bottom first.
+    layers: Vec<Game>,
+    /// The layer the selector is on.
+    layer: usize,
+    scores: Vec<u32>,
+    session: Session,
+}
+
+#[async_trait]
+impl Component for CubeGame {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+        match config::key_action(&key_event) {
+            Some(action) => Ok(self.act(action)),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        if let Some(clicked) = self.session.point(&mouse_event) {
+            return Ok(if clicked { self.act(KeyAction::Confirm) } else { Action::None });
+        }
+        if let Some(layer) = self.layers.iter().position(|game| game.is_over(&mouse_event)) {
+            if layer != self.layer {
+                self.change_layer(layer);
+            }
+            if self.layers[layer].point(&mouse_event) {
+                return Ok(self.act(KeyAction::Confirm));
+            }
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        self.record_result();
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Percentage(60),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(area);
+        let reversed = Style::new().add_modifier(Modifier::REVERSED);
+        let players = config::players();
+        let name = |mark| players.name(mark).to_string();
+        let text = match self.board.winner() {
+            Square::None => Text::from(self.session.scoreboard(&self.scores, self.board.turn(), name, false)),
+            winner => self.session.ending(winner, name),
+        };
+        frame.render_widget(Paragraph::new(text).centered(), layout[1]);
+        self.session.render_status(frame, layout[6]);
+
+        let size = self.board.size();
+        let boards = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Fill(1); size])
+            .spacing(2)
+            .horizontal_margin(2)
+            .split(layout[3]);
+        let labels = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Fill(1); size])
+            .spacing(2)
+            .horizontal_margin(2)
+            .split(layout[4]);
+        for (layer, game) in self.layers.iter_mut().enumerate() {
+            game.render(frame, boards[layer]);
+            let label = Paragraph::new(format!("Layer {}", layer + 1)).centered();
+            frame.render_widget(if layer == self.layer { label.style(reversed) } else { label }, labels[layer]);
+        }
+
+        self.session.render_over(frame, layout[3], vec![format!("Size {:>11}", format!("{size}×{size}×{size}"))]);
+    }
+}
+
+impl CubeGame {
+    /// A game on the 4×4×4 cube, whose size can still be changed in the setup.
+    pub fn new() -> Self {
+        let config = config::get();
+        let mut board = CubeBoard::new(4);
+        board.set_first(config.defaults.first.mark());
+        let mut cube_game = CubeGame {
+            board,
+            layers: Vec::new(),
+            layer: 0,
+            scores: vec![0; 2],
+            session: Session::new(&MENU),
+        };
+        cube_game.replace_board(4);
+        cube_game
+    }
+
+    fn act(&mut self, action: KeyAction) -> Action {
+        match action {
+            KeyAction::Quit => return self.quit(),
+            KeyAction::Help => return Action::Push(Box::new(Help::new())),
+            _ => {}
+        }
+        match self.session.choose(action) {
+            Choice::None | Choice::Item(MenuItem::Resume) => {}
+            Choice::Start => self.sync(),
+            Choice::Change(_, delta) => self.replace_board(self.board.size().saturating_add_signed(delta).clamp(3, MAX_SIZE)),
+            Choice::Item(MenuItem::Undo) => self.undo(),
+            Choice::Item(MenuItem::Redo) => self.redo(),
+            Choice::Item(MenuItem::Rematch) => self.replace_board(self.board.size()),
+            Choice::Item(MenuItem::Restart) => {
+                self.replace_board(self.board.size());
+                self.scores = vec![0; 2];
+            }
+            Choice::Item(MenuItem::Menu) => return Action::Pop,
+            Choice::Item(MenuItem::Quit) => return self.quit(),
+            Choice::Item(_) => {}
+            Choice::Board => match action {
+                KeyAction::Undo => self.undo(),
+                KeyAction::Redo => self.redo(),
+                _ if self.board.winner() != Square::None => self.session.has_menu_open = true,
+                KeyAction::Back => self.session.has_menu_open = true,
+                KeyAction::NextLayer => self.change_layer((self.layer + 1).min(self.board.size() - 1)),
+                KeyAction::PreviousLayer => self.change_layer(self.layer.saturating_sub(1)),
+                KeyAction::Confirm => {
+                    // Occupied squares are simply ignored, like in the normal game.
+                    let _ = self.play((self.layer, self.layers[self.layer].selected));
+                }
+                KeyAction::Up | KeyAction::Down | KeyAction::Left | KeyAction::Right => self.layers[self.layer].act(action),
+                _ => {}
+            },
+        }
+        Action::None
+    }
+
+    fn quit(&self) -> Action {
+        session::quit(!self.board.moves().is_empty() && self.board.winner() == Square::None)
+    }
+
+    /// Moves the selector to the same square of another layer.
+    fn change_layer(&mut self, layer: usize) {
+        self.layers[layer].selected = self.layers[self.layer].selected;
+        self.layer = layer;
+        self.sync();
+    }
+
+    fn play(&mut self, mv: CubeMove) -> Result<Square, MoveError> {
+        let winner = self.board.play(mv)?;
+        self.finish_move(winner);
+        Ok(winner)
+    }
+
+    fn finish_move(&mut self, winner: Square) {
+        if winner == Square::Draw {
+            self.session.has_menu_open = true;
+        } else if winner != Square::None {
+            self.scores[winner.index()] += 1;
+            self.session.has_menu_open = true;
+        }
+        self.sync();
+    }
+
+    /// Takes back the last move, including the point it may have scored.
+    fn undo(&mut self) {
+        let winner = self.board.winner();
+        let Some((layer, square)) = self.board.undo() else {
+            return;
+        };
+        if !matches!(winner, Square::None | Square::Draw) {
+            let score = &mut self.scores[winner.index()];
+            *score = score.checked_sub(1).expect("the winner of the game taken back scored a point for it");
+        }
+        self.session.recorded = false;
+        self.layer = layer;
+        self.layers[layer].selected = square;
+        self.sync();
+    }
+
+    fn redo(&mut self) {
+        if let Some(winner) = self.board.redo() {
+            self.finish_move(winner);
+        }
+    }
+
+    /// Starts over on an empty cube of `size`, keeping who moves first.
+    fn replace_board(&mut self, size: usize) {
+        let first = self.board.first();
+        self.board = CubeBoard::new(size);
+        self.board.set_first(first);
+        self.layers = (0..size).map(|_| Game::new()).collect();
+        for game in &mut self.layers {
+            game.selected = (size / 2, size / 2);
+        }
+        self.layer = 0;
+        self.session.start_over();
+        self.sync();
+    }
+
+    /// Copies the layers of the cube into their views, with the winning line framed on each
+    /// layer it crosses.
+    fn sync(&mut self) {
+        let playing = !self.session.in_setup && self.board.winner() == Square::None;
+        for (layer, game) in self.layers.iter_mut().enumerate() {
+            game.board = self.board.layer(layer).clone();
+            game.highlighted = self.board.line().iter().filter(|(other, _)| *other == layer).map(|(_, square)| *square).collect();
+            game.show_selector = playing && layer == self.layer;
+        }
+    }
+
+    /// Adds the game to the statistics once it is over.
+    fn record_result(&mut self) {
+        let winner = self.board.winner();
+        if !self.session.needs_recording(winner) {
+            return;
+        }
+        let variant = Variant::Cube { size: self.board.size() };
+        let players = config::players();
+        let result = GameResult::new(variant, players.name(Square::X), players.name(Square::Circle), self.board.first(), winner, self.board.moves().len());
+        self.session.record(&result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+    use crate::engine::lines;
+
+    fn harness() -> Harness<CubeGame> {
+        Harness::with_size(CubeGame::new(), 100, 30)
+    }
+
+    #[test]
+    fn every_line_of_the_cube_counts() {
+        assert_eq!(lines(3).len(), 49);
+        assert_eq!(lines(4).len(), 76);
+        let space_diagonal: Vec<CubeMove> = (0..4).map(|i| (i, (i, 3 - i))).collect();
+        assert!(lines(4).contains(&space_diagonal));
+    }
+
+    #[tokio::test]
+    async fn brackets_move_the_selector_between_layers() {
+        let mut harness = harness();
+        harness.keys(&["l", "Enter", "]", "]", "Enter", "[", "Enter"]).await;
+        assert_eq!(harness.component.board.size(), 5);
+        assert_eq!(harness.component.board.moves(), [(2, (2, 2)), (1, (2, 2))]);
+        assert!(harness.contains("0 Player1 | Player2 0"));
+        assert!(harness.contains("Layer 5"));
+    }
+
+    #[tokio::test]
+    async fn space_diagonal_wins_and_is_framed_on_every_layer() {
+        let mut harness = harness();
+        harness.keys(&["Enter"]).await;
+        for i in 0..3 {
+            harness.component.play((i, (i, i))).unwrap();
+            harness.component.play((i, (3 - i, i))).unwrap();
+        }
+        assert_eq!(harness.component.play((3, (3, 3))), Ok(Square::X));
+        assert_eq!(harness.component.scores, [1, 0]);
+        for layer in 0..4 {
+            assert_eq!(harness.component.layers[layer].highlighted, [(layer, layer)]);
+        }
+        harness.render();
+        assert!(harness.contains("Player1 wins!"));
+        assert!(harness.contains("Rematch"));
+
+        harness.keys(&["Esc", "u"]).await;
+        assert_eq!(harness.component.scores, [0, 0]);
+        assert!(harness.component.layers[0].highlighted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rematch_keeps_the_scores_and_restart_clears_them() {
+        let mut harness = harness();
+        harness.keys(&["Enter"]).await;
+        for i in 0..3 {
+            harness.component.play((i, (i, 0))).unwrap();
+            harness.component.play((i, (i, 1))).unwrap();
+        }
+        harness.component.play((3, (3, 0))).unwrap();
+        harness.component.update().await.unwrap();
+        assert!(harness.component.session.recorded);
+
+        // Rematch is the fourth item, Restart the fifth.
+        harness.keys(&["j", "j", "j", "Enter"]).await;
+        assert!(harness.component.board.moves().is_empty());
+        assert!(!harness.component.session.recorded);
+        assert!(harness.contains("1 Player1 | Player2 0"));
+        harness.keys(&["Esc", "j", "j", "j", "j", "Enter"]).await;
+        assert_eq!(harness.component.scores, [0, 0]);
+        assert!(!harness.contains("Resume"));
+    }
+}
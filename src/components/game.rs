@@ -1,48 +1,66 @@
+use std::time::{Duration, Instant};
 use ratatui::widgets::canvas::{Painter, Rectangle, Shape};
 use async_trait::async_trait;
-use ratatui::crossterm::event::KeyCode::Char;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::widgets::canvas::{Canvas, Circle, Line};
 use crate::action::Action;
 use crate::components::Component;
-use crate::components::game::Square::{Draw, X};
+use crate::config;
+use crate::engine::{Board, MoveError, Outcome, Position, Rules, Square};
+use crate::engine::Square::X;
+use crate::keymap::KeyAction;
+
+/// How long a dropped mark takes to fall by one row with gravity.
+const DROP_TIME: Duration = Duration::from_millis(40);
 
 #[derive(Clone)]
 pub struct Game {
-    pub selected: (f64, f64),
-    pub board: Vec<Vec<Square>>,
-    pub scores: (u8, u8),
-    pub winner: (Square, f64, f64, f64, f64),
-    pub turn: Square,
+    pub selected: Position,
+    pub board: Board,
+    /// Points of each player, in the order of [`MARKS`].
+    pub scores: Vec<u32>,
     pub line_color: Color,
     pub show_selector: bool,
+    /// The mark placed in wild games, where players choose between X and O.
+    pub placing: Square,
+    /// Squares framed in the winning line's color, where a line of a 3D game crosses this layer.
+    pub highlighted: Vec<Position>,
+    area: Rect,
+    /// When the last move was dropped, while it is still falling.
+    dropped: Option<Instant>,
 }
 
 #[async_trait]
 impl Component for Game {
     async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
-        match key_event.code {
-            Char('k') | KeyCode::Up => self.selected.1 = (self.selected.1 + 1.0).min(self.board.len() as f64 - 1.0),
-            Char('j') | KeyCode::Down => self.selected.1 = (self.selected.1 - 1.0).max(0.0),
-            Char('l') | KeyCode::Right => self.selected.0 = (self.selected.0 + 1.0).min(self.board.len() as f64 - 1.0),
-            Char('h') | KeyCode::Left => self.selected.0 = (self.selected.0 - 1.0).max(0.0),
-            KeyCode::Enter => self.hit(),
-            _ => ()
+        if let Some(action) = config::key_action(&key_event) {
+            self.act(action);
+        }
+        Ok(Action::None)
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        if self.point(&mouse_event) {
+            self.hit();
         }
         Ok(Action::None)
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.area = area;
+        let colors = config::colors();
         let canvas = Canvas::default()
             .x_bounds([0.0, 100.0])
             .y_bounds([0.0, 100.0])
             .paint(|ctx| {
-                let shift = 100.0/self.board.len() as f64;
-                for i in 1..self.board.len() {
-                    let shifted = shift * i as f64;
+                let shift_x = 100.0/self.board.width() as f64;
+                let shift_y = 100.0/self.board.height() as f64;
+                let radius = shift_x.min(shift_y) * 0.3;
+                for i in 1..self.board.width() {
+                    let shifted = shift_x * i as f64;
                     ctx.draw(&Line {
                         x1: shifted,
                         y1: 0.0,
@@ -50,6 +68,9 @@ impl Component for Game {
                         y2: 100.0,
                         color: self.line_color,
                     });
+                }
+                for i in 1..self.board.height() {
+                    let shifted = shift_y * i as f64;
                     ctx.draw(&Line {
                         x1: 0.0,
                         y1: shifted,
@@ -59,47 +80,88 @@ impl Component for Game {
                     });
                 }
 
-                if self.show_selector == true {
+                let falling = self.falling();
+                for (x, y) in &self.highlighted {
+                    ctx.draw(&Rectangle {
+                        x: 0.1*shift_x + shift_x * *x as f64,
+                        y: 0.1*shift_y + shift_y * *y as f64,
+                        color: colors.winning_line,
+                        height: shift_y*0.8,
+                        width: shift_x*0.8,
+                    });
+                }
+                if self.show_selector {
                     ctx.draw(&Rectangle {
-                        x: 0.1*shift + shift * self.selected.0,
-                        y: 0.1*shift + shift * self.selected.1,
-                        color: Color::Green,
-                        height: shift*0.8,
-                        width: shift*0.8,
+                        x: 0.1*shift_x + shift_x * self.selected.0 as f64,
+                        y: 0.1*shift_y + shift_y * self.selected.1 as f64,
+                        color: colors.selector,
+                        height: shift_y*0.8,
+                        width: shift_x*0.8,
                     });
+                    // A smaller mark in the selector shows which one a wild move places.
+                    if self.board.rules().wild && self.board.get(self.selected) == Square::None {
+                        let (x, y) = (shift_x * (0.5 + self.selected.0 as f64), shift_y * (0.5 + self.selected.1 as f64));
+                        if self.placing == X {
+                            ctx.draw(&Cross { x, y, radius: radius * 0.6, color: colors.selector });
+                        } else {
+                            ctx.draw(&Circle { x, y, radius: radius * 0.6, color: colors.selector });
+                        }
+                    }
                 }
-                for x in 0..self.board.len() {
-                    for y in 0..self.board.len() {
+                for x in 0..self.board.width() {
+                    for y in 0..self.board.height() {
+                        let square = self.board.get((x, y));
                         let x = x as f64;
-                        let y = y as f64;
-                        match self.board.get(x as usize).unwrap().get(y as usize).unwrap() {
+                        // The mark still falling is drawn above where it lands.
+                        let y = match falling {
+                            Some((mv, height)) if mv == (x as usize, y) => height,
+                            _ => y as f64,
+                        };
+                        match square {
                             Square::Circle => {
                                 ctx.draw(&Circle {
-                                    x: shift * (0.5 + x),
-                                    y: shift * (0.5 + y),
-                                    radius: shift * 0.3,
-                                    color: Color::Yellow,
+                                    x: shift_x * (0.5 + x),
+                                    y: shift_y * (0.5 + y),
+                                    radius,
+                                    color: colors.o,
                                 })
                             }
                             X => {
                                 ctx.draw(&Cross {
-                                    x: shift * (0.5 + x),
-                                    y: shift * (0.5 + y),
-                                    radius: shift * 0.3,
-                                    color: Color::Cyan,
+                                    x: shift_x * (0.5 + x),
+                                    y: shift_y * (0.5 + y),
+                                    radius,
+                                    color: colors.x,
+                                });
+                            }
+                            Square::Triangle => {
+                                ctx.draw(&Triangle {
+                                    x: shift_x * (0.5 + x),
+                                    y: shift_y * (0.5 + y),
+                                    radius,
+                                    color: colors.triangle,
+                                });
+                            }
+                            Square::Block => {
+                                ctx.draw(&Block {
+                                    x: shift_x * (0.5 + x),
+                                    y: shift_y * (0.5 + y),
+                                    radius,
+                                    color: colors.block,
                                 });
                             }
                             _ => {}
                         }
                     }
                 }
-                if self.winner.0 != Square::None && self.winner.0 != Draw {
+                if let (Outcome::Win(_, line), None) = (self.board.outcome(), falling) {
+                    let (dx, dy) = line.direction();
                     ctx.draw(&Line {
-                        x1: self.winner.1,
-                        y1: self.winner.2,
-                        x2: self.winner.3,
-                        y2: self.winner.4,
-                        color: Color::Red,
+                        x1: (line.start.0 as f64 + 0.5) * shift_x - dx as f64 * shift_x * 0.4,
+                        y1: (line.start.1 as f64 + 0.5) * shift_y - dy as f64 * shift_y * 0.4,
+                        x2: (line.end.0 as f64 + 0.5) * shift_x + dx as f64 * shift_x * 0.4,
+                        y2: (line.end.1 as f64 + 0.5) * shift_y + dy as f64 * shift_y * 0.4,
+                        color: colors.winning_line,
                     })
                 }
             });
@@ -109,121 +171,203 @@ impl Component for Game {
 
 impl Game {
     pub fn new() -> Self {
-        Game{ selected: (1.0, 1.0), board: vec![vec![Square::None; 3]; 3], scores: (0, 0), winner: (Square::None, 0.0, 0.0, 0.0, 0.0), turn: X, line_color: Color::White, show_selector: true }
+        Game { selected: (1, 1), board: Board::new(3, 3, 3), scores: vec![0; 2], line_color: config::colors().grid, show_selector: true, placing: X, highlighted: Vec::new(), area: Rect::default(), dropped: None }
     }
 
-    pub fn hit(&mut self) {
-        let board_size = self.board.len() as f64;
-        let selected_x = self.selected.0;
-        let selected_y = self.selected.1;
-        if self.board[selected_x as usize][selected_y as usize] != Square::None || self.winner.0 != Square::None {
-            return;
-        }
-
-        for relative_x in if selected_x == 0.0 { 0 } else { -1 }..if selected_x == board_size-1.0 { 1 } else { 2 } {
-            for relative_y in if selected_y == 0.0 { 0 } else { -1 }..if selected_y == board_size-1.0 { 1 } else { 2 } {
-                let relative_x = relative_x as f64;
-                let relative_y = relative_y as f64;
-                if relative_x == 0.0 && relative_y == 0.0 {
-                    continue;
-                }
-                let mut base_x = selected_x;
-                let mut base_y = selected_y;
-
-                let mut top_x = selected_x;
-                let mut top_y = selected_y;
-
-                let mut fail = false;
-                let mut count = 1;
-                while base_x-relative_x < board_size && base_x-relative_x >= 0.0 && base_y-relative_y < board_size && base_y-relative_y >= 0.0 {
-                    if self.board[(base_x-relative_x) as usize][(base_y-relative_y) as usize] == self.turn {
-                        base_x -= relative_x;
-                        base_y -= relative_y;
-                        count += 1;
-                    } else {
-                        fail = true;
-                        break;
-                    }
-                }
-                if fail {continue}
-
-                while top_x+relative_x < board_size && top_x+relative_x >= 0.0 && top_y+relative_y < board_size && top_y+relative_y >= 0.0 {
-                    if self.board[(top_x+relative_x) as usize][(top_y+relative_y) as usize] == self.turn {
-                        top_x += relative_x;
-                        top_y += relative_y;
-                        count += 1;
-                    } else {
-                        fail = true;
-                        break;
-                    }
-                }
-                if fail || count as f64 != board_size {continue}
-                if self.turn == X {
-                    self.scores.0 += 1;
-                } else {
-                    self.scores.1 += 1;
-                }
-                let shift = 100.0/self.board.len() as f64;
-                self.winner = (self.turn,
-                               (base_x+0.5) * shift - relative_x * shift * 0.4,
-                               (base_y+0.5) * shift - relative_y * shift * 0.4,
-                               (top_x+0.5) * shift + relative_x * shift * 0.4,
-                               (top_y+0.5) * shift + relative_y * shift * 0.4,
-                );
-                self.show_selector = false;
-                break;
-            }
+    /// Whether a dropped mark is still falling and the board needs drawing again.
+    pub fn is_animating(&self) -> bool {
+        self.falling().is_some()
+    }
+
+    /// The last move and the row it has fallen to so far, while it is falling.
+    fn falling(&self) -> Option<(Position, f64)> {
+        let dropped = self.dropped?;
+        let &mv = self.board.moves().last()?;
+        let fallen = dropped.elapsed().as_secs_f64() / DROP_TIME.as_secs_f64();
+        let height = (self.board.height() - 1) as f64 - fallen;
+        (height > mv.1 as f64).then_some((mv, height))
+    }
+
+    /// Whether the mouse is over where the board was last drawn.
+    pub fn is_over(&self, mouse_event: &MouseEvent) -> bool {
+        let area = self.area;
+        mouse_event.column >= area.left() && mouse_event.column < area.right() && mouse_event.row >= area.top() && mouse_event.row < area.bottom()
+    }
+
+    /// Moves the selector to the square under the mouse. Returns whether it was clicked.
+    pub fn point(&mut self, mouse_event: &MouseEvent) -> bool {
+        let area = self.area;
+        if !self.show_selector || !self.is_over(mouse_event) || !matches!(mouse_event.kind, MouseEventKind::Moved | MouseEventKind::Down(MouseButton::Left)) {
+            return false;
         }
+        // Rows are counted from the top of the screen but from the bottom of the board.
+        let x = (mouse_event.column - area.left()) as usize * self.board.width() / area.width as usize;
+        let y = (area.bottom() - 1 - mouse_event.row) as usize * self.board.height() / area.height as usize;
+        self.selected = (x, y);
+        self.snap_selector();
+        mouse_event.kind == MouseEventKind::Down(MouseButton::Left)
+    }
 
-        match self.turn {
-            X => {
-                self.board[selected_x as usize][selected_y as usize] = X;
-                self.turn = Square::Circle;
-            }
-            Square::Circle => {
-                self.board[selected_x as usize][selected_y as usize] = Square::Circle;
-                self.turn = X
+    /// Moves the selector or plays the selected square. With gravity only the column is chosen.
+    pub fn act(&mut self, action: KeyAction) {
+        let gravity = self.board.rules().gravity;
+        match action {
+            KeyAction::Up if !gravity => self.selected.1 = (self.selected.1 + 1).min(self.board.height() - 1),
+            KeyAction::Down if !gravity => self.selected.1 = self.selected.1.saturating_sub(1),
+            KeyAction::Right => self.selected.0 = (self.selected.0 + 1).min(self.board.width() - 1),
+            KeyAction::Left => self.selected.0 = self.selected.0.saturating_sub(1),
+            KeyAction::Confirm => self.hit(),
+            KeyAction::SwitchMark if self.board.rules().wild => self.placing = self.placing.opponent(),
+            KeyAction::Cell(cell) if self.is_numpad_sized() => {
+                self.selected = cell;
+                self.hit();
             }
             _ => ()
         }
-        self.selected = ((self.board.len()/2) as f64, (self.board.len()/2) as f64);
+        self.snap_selector();
+    }
 
-        if self.winner.0 == Square::None {
-            let mut cancel = true;
-            self.board.iter().for_each(|s| s.iter().for_each(|slot| {if slot == &Square::None {cancel = false}}));
-            if cancel {
-                self.winner.0 = Draw;
-            }
+    /// With gravity, keeps the selector on the square a mark dropped in its column lands on, or at
+    /// the top of a full column.
+    fn snap_selector(&mut self) {
+        if self.board.rules().gravity {
+            self.selected.1 = self.board.landing(self.selected.0).map_or(self.board.height() - 1, |(_, y)| y);
         }
     }
 
+    /// Whether the numeric keypad maps onto the board.
+    pub fn is_numpad_sized(&self) -> bool {
+        self.board.width() == 3 && self.board.height() == 3
+    }
+
+    pub fn winner(&self) -> Square {
+        self.board.outcome().winner()
+    }
+
+    pub fn hit(&mut self) {
+        self.snap_selector();
+        let mark = if self.board.rules().wild { self.placing } else { self.board.turn() };
+        // Occupied squares are simply ignored.
+        let _ = self.play_mark(self.selected, mark);
+    }
+
+    /// Plays `mv` for the player to move, keeping score and recentering the selector.
+    pub fn play(&mut self, mv: Position) -> Result<Outcome, MoveError> {
+        self.play_mark(mv, self.board.turn())
+    }
+
+    /// Plays `mv` placing `mark`, which only wild games let differ from the player to move.
+    pub fn play_mark(&mut self, mv: Position, mark: Square) -> Result<Outcome, MoveError> {
+        let outcome = self.board.play_mark(mv, mark)?;
+        self.finish_move(outcome);
+        Ok(outcome)
+    }
+
+    /// Takes back the last move, including the point it may have scored. Returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        let outcome = self.board.outcome();
+        let Some(mv) = self.board.undo() else {
+            return false;
+        };
+        if let Outcome::Win(square, _) = outcome {
+            self.take_point(square);
+        }
+        self.selected = mv;
+        self.show_selector = true;
+        self.dropped = None;
+        true
+    }
+
+    /// Takes back the point `winner` scored, if they still have it: a loaded game may come with fewer
+    /// points than its board shows won.
+    pub fn take_point(&mut self, winner: Square) {
+        if let Some(score) = self.scores[winner.index()].checked_sub(1) {
+            self.scores[winner.index()] = score;
+        }
+    }
+
+    /// Plays the last undone move again. Returns whether there was one.
+    pub fn redo(&mut self) -> bool {
+        let Some(outcome) = self.board.redo() else {
+            return false;
+        };
+        self.finish_move(outcome);
+        true
+    }
+
+    fn finish_move(&mut self, outcome: Outcome) {
+        if self.board.rules().gravity {
+            self.dropped = Some(Instant::now());
+        }
+        if let Outcome::Win(square, _) = outcome {
+            self.scores[square.index()] += 1;
+            self.show_selector = false;
+        }
+        self.center_selector();
+    }
+
     pub fn restart(&mut self) {
         self.rematch();
-        self.scores = (0, 0);
+        self.scores = vec![0; self.board.players()];
     }
 
     pub fn rematch(&mut self) {
-        self.board = vec![vec![Square::None; self.board.len()]; self.board.len()];
-        self.winner = (Square::None, 0.0, 0.0, 0.0, 0.0);
-        self.turn = X;
+        self.replace_board(self.board.width(), self.board.height(), self.board.win_length());
         self.show_selector = true;
-        self.selected = ((self.board.len()/2) as f64, (self.board.len()/2) as f64);
+        self.center_selector();
     }
 
-    pub fn set_size(&mut self, num: usize) {
-        if num > 2 {
-            self.selected = ((num/2) as f64, (num/2) as f64);
-            self.board = vec![vec![Square::None; num]; num]
+    pub fn set_size(&mut self, width: usize, height: usize) {
+        if width > 2 && height > 2 {
+            self.replace_board(width, height, self.board.win_length());
+            self.center_selector();
+        }
+    }
+
+    pub fn set_win_length(&mut self, num: usize) {
+        if num > 2 && num <= self.board.width().max(self.board.height()) {
+            self.replace_board(self.board.width(), self.board.height(), num);
         }
     }
-}
 
-#[derive(PartialEq, Eq, Copy, Clone)]
-pub enum Square {
-    Circle,
-    X,
-    None,
-    Draw,
+    /// Plays this and following games by `rules`. Only takes effect before the first move.
+    pub fn set_rules(&mut self, rules: Rules) {
+        if self.board.moves().is_empty() {
+            self.board.set_rules(rules);
+            self.center_selector();
+        }
+    }
+
+    /// Lets `players` take turns in this and following games, starting everyone from zero points.
+    /// Only takes effect before the first move.
+    pub fn set_players(&mut self, players: usize) {
+        if self.board.moves().is_empty() {
+            self.board.set_players(players);
+            self.scores = vec![0; self.board.players()];
+        }
+    }
+
+    /// Lets `first` start this and following games. Only takes effect before the first move.
+    pub fn set_first(&mut self, first: Square) {
+        if self.board.moves().is_empty() {
+            self.board.set_turn(first);
+        }
+    }
+
+    /// Starts over on a new board, keeping who moves first.
+    fn replace_board(&mut self, width: usize, height: usize, win_length: usize) {
+        let (first, rules, players) = (self.board.first(), self.board.rules(), self.board.players());
+        self.board = Board::new(width, height, win_length);
+        self.board.set_players(players);
+        self.board.set_turn(first);
+        self.board.set_rules(rules);
+        self.dropped = None;
+    }
+
+    fn center_selector(&mut self) {
+        self.selected = (self.board.width() / 2, self.board.height() / 2);
+        self.snap_selector();
+    }
 }
 
 pub struct Cross {
@@ -250,4 +394,157 @@ impl Shape for Cross {
             color: self.color,
         }.draw(painter);
     }
-}
\ No newline at end of file
+}
+
+/// A triangle standing on its base.
+pub struct Triangle {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub color: Color,
+}
+
+impl Shape for Triangle {
+    fn draw(&self, painter: &mut Painter) {
+        let corners = [(self.x, self.y+self.radius), (self.x-self.radius, self.y-self.radius), (self.x+self.radius, self.y-self.radius)];
+        for (i, (x1, y1)) in corners.into_iter().enumerate() {
+            let (x2, y2) = corners[(i + 1) % corners.len()];
+            Line { x1, y1, x2, y2, color: self.color }.draw(painter);
+        }
+    }
+}
+
+/// A square, the fourth player's mark.
+pub struct Block {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub color: Color,
+}
+
+impl Shape for Block {
+    fn draw(&self, painter: &mut Painter) {
+        Rectangle {
+            x: self.x-self.radius*0.8,
+            y: self.y-self.radius*0.8,
+            width: self.radius*1.6,
+            height: self.radius*1.6,
+            color: self.color,
+        }.draw(painter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+
+    fn game(width: usize, height: usize, win_length: usize) -> Game {
+        let mut game = Game::new();
+        game.set_size(width, height);
+        game.set_win_length(win_length);
+        game
+    }
+
+    /// Plays `moves` alternately for both players by pointing the selector and hitting.
+    fn hit_all(game: &mut Game, moves: &[Position]) {
+        for &mv in moves {
+            game.selected = mv;
+            game.hit();
+        }
+    }
+
+    #[test]
+    fn row_wins_on_the_normal_board() {
+        let mut game = game(3, 3, 3);
+        hit_all(&mut game, &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(game.winner(), Square::None);
+        hit_all(&mut game, &[(2, 0)]);
+        assert_eq!(game.winner(), X);
+        assert_eq!(game.scores, [1, 0]);
+        assert!(!game.show_selector);
+    }
+
+    #[test]
+    fn full_board_without_a_line_is_a_draw() {
+        let mut game = game(3, 3, 3);
+        hit_all(&mut game, &[(0, 2), (1, 2), (2, 2), (1, 1), (0, 1), (0, 0), (1, 0), (2, 1)]);
+        assert_eq!(game.winner(), Square::None);
+        hit_all(&mut game, &[(2, 0)]);
+        assert_eq!(game.winner(), Square::Draw);
+        assert_eq!(game.scores, [0, 0]);
+    }
+
+    #[test]
+    fn diagonal_wins_with_a_shorter_win_length() {
+        let mut game = game(5, 5, 4);
+        hit_all(&mut game, &[(0, 0), (0, 4), (1, 1), (1, 4), (2, 2), (2, 4)]);
+        assert_eq!(game.winner(), Square::None);
+        hit_all(&mut game, &[(3, 3)]);
+        assert_eq!(game.winner(), X);
+    }
+
+    #[test]
+    fn column_wins_on_a_rectangular_board() {
+        let mut game = game(4, 3, 3);
+        hit_all(&mut game, &[(3, 0), (0, 0), (3, 1), (1, 0), (0, 2), (3, 2), (1, 2), (2, 0)]);
+        assert_eq!(game.winner(), Square::Circle);
+        assert_eq!(game.scores, [0, 1]);
+    }
+
+    #[test]
+    fn line_shorter_than_the_win_length_does_not_win() {
+        let mut game = game(5, 5, 5);
+        hit_all(&mut game, &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1), (3, 0), (3, 1)]);
+        assert_eq!(game.winner(), Square::None);
+        hit_all(&mut game, &[(4, 0)]);
+        assert_eq!(game.winner(), X);
+    }
+
+    #[test]
+    fn hitting_an_occupied_square_is_ignored() {
+        let mut game = game(3, 3, 3);
+        hit_all(&mut game, &[(1, 1), (1, 1)]);
+        assert_eq!(game.board.moves(), [(1, 1)]);
+        assert_eq!(game.board.turn(), Square::Circle);
+    }
+
+    #[tokio::test]
+    async fn keys_move_the_selector_within_the_board() {
+        let mut harness = Harness::new(game(3, 3, 3));
+        harness.keys(&["h", "h", "h", "Up", "k", "k"]).await;
+        assert_eq!(harness.component.selected, (0, 2));
+        // Playing recenters the selector.
+        harness.keys(&["Enter"]).await;
+        assert_eq!(harness.component.selected, (1, 1));
+        harness.keys(&["l", "Right", "Right", "j", "Enter"]).await;
+        assert_eq!(harness.component.board.moves(), [(0, 2), (2, 0)]);
+        assert_eq!(harness.component.selected, (1, 1));
+    }
+
+    #[tokio::test]
+    async fn with_gravity_marks_drop_down_the_chosen_column() {
+        let mut game = game(7, 6, 4);
+        game.set_rules(Rules { gravity: true, ..Rules::default() });
+        let mut harness = Harness::new(game);
+        harness.keys(&["k", "k", "Enter", "Enter", "h", "Enter"]).await;
+        assert_eq!(harness.component.board.moves(), [(3, 0), (3, 1), (2, 0)]);
+        assert_eq!(harness.component.selected, (3, 2));
+        assert!(harness.component.is_animating());
+        assert_eq!(harness.component.board.clone().play((4, 3)), Err(MoveError::Floating));
+    }
+
+    #[test]
+    fn with_gravity_four_stacked_in_a_column_win() {
+        let mut game = game(7, 6, 4);
+        game.set_rules(Rules { gravity: true, ..Rules::default() });
+        for column in [0, 1, 0, 1, 0, 1] {
+            game.selected = (column, 5);
+            game.act(KeyAction::Confirm);
+        }
+        assert_eq!(game.winner(), Square::None);
+        game.selected = (0, 5);
+        game.act(KeyAction::Confirm);
+        assert_eq!(game.winner(), X);
+    }
+}
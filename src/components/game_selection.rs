@@ -1,18 +1,24 @@
 use async_trait::async_trait;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use ratatui::crossterm::event::KeyCode::Char;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::widgets::{List, ListState, Paragraph};
 use crate::action::Action;
-use crate::components::Component;
+use crate::components::{point_at_item, Component};
+use crate::components::cube_game::CubeGame;
+use crate::components::help::Help;
 use crate::components::local_game::LocalGame;
-use crate::components::main_menu::MainMenu;
 use crate::components::super_local_game::SuperLocalGame;
+use crate::config;
+use crate::config::Mode;
+use crate::keymap::KeyAction;
+
+const GAME_MODES: [&str; 7] = ["Normal", "Super", "Gravity", "Qubic 3D", "vs Computer", "Super vs Computer", "Back"];
 
 pub struct GameSelection {
     list_state: ListState,
+    list_area: Rect,
 }
 
 #[async_trait]
@@ -21,49 +27,69 @@ impl Component for GameSelection {
         if key_event.kind != KeyEventKind::Press {
             return Ok(Action::None)
         }
-        match key_event.code {
-            Char('q') => return Ok(Action::Quit),
-            Char('j') | KeyCode::Down => self.list_state.select_next(),
-            Char('k') | KeyCode::Up => self.list_state.select_previous(),
-            KeyCode::Enter => {
-                match self.list_state.selected().unwrap() {
-                    0 => return Ok(Action::ChangeComponent(Box::new(LocalGame::new()))),
-                    1 => return Ok(Action::ChangeComponent(Box::new(SuperLocalGame::new()))),
-                    2 => return Ok(Action::ChangeComponent(Box::new(MainMenu::new()))),
-                    _ => ()
-                }
-            }
-            KeyCode::Backspace => return Ok(Action::ChangeComponent(Box::new(MainMenu::new()))),
-            _ => ()
+        match config::key_action(&key_event) {
+            Some(action) => self.act(action),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        if point_at_item(&mouse_event, self.list_area, &mut self.list_state, GAME_MODES.len()) {
+            return self.act(KeyAction::Confirm);
         }
         Ok(Action::None)
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let game_modes = ["Normal", "Super", "Back"];
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
                 Constraint::Fill(1),
                 Constraint::Length(2),
-                Constraint::Length(game_modes.len() as u16),
+                Constraint::Length(GAME_MODES.len() as u16),
                 Constraint::Fill(1)]
             )
             .split(Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints(vec![
                     Constraint::Fill(1),
-                    Constraint::Length(11),
+                    Constraint::Length(17),
                     Constraint::Fill(1)]
                 ).split(area)[1]
             );
         frame.render_widget(Paragraph::new("Tic Tac Toe"), layout[1]);
-        frame.render_stateful_widget(List::new(game_modes).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        frame.render_stateful_widget(List::new(GAME_MODES).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        self.list_area = layout[2];
     }
 }
 impl GameSelection {
     pub fn new() -> Self {
-        let list_state = ListState::default().with_selected(Some(0));
-        GameSelection { list_state }
+        let selected = if config::get().defaults.mode == Mode::Super { 1 } else { 0 };
+        let list_state = ListState::default().with_selected(Some(selected));
+        GameSelection { list_state, list_area: Rect::default() }
+    }
+
+    fn act(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        match action {
+            KeyAction::Quit => return Ok(Action::Quit),
+            KeyAction::Help => return Ok(Action::Push(Box::new(Help::new()))),
+            KeyAction::Down => self.list_state.select_next(),
+            KeyAction::Up => self.list_state.select_previous(),
+            KeyAction::Confirm => {
+                match self.list_state.selected().unwrap() {
+                    0 => return Ok(Action::Replace(Box::new(LocalGame::new()))),
+                    1 => return Ok(Action::Replace(Box::new(SuperLocalGame::new()))),
+                    2 => return Ok(Action::Replace(Box::new(LocalGame::gravity()))),
+                    3 => return Ok(Action::Replace(Box::new(CubeGame::new()))),
+                    4 => return Ok(Action::Replace(Box::new(LocalGame::vs_computer()))),
+                    5 => return Ok(Action::Replace(Box::new(SuperLocalGame::vs_computer()))),
+                    6 => return Ok(Action::Pop),
+                    _ => ()
+                }
+            }
+            KeyAction::Back => return Ok(Action::Pop),
+            _ => ()
+        }
+        Ok(Action::None)
     }
 }
\ No newline at end of file
@@ -0,0 +1,62 @@
+//! Drives a component without a terminal, for tests.
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::Terminal;
+use crate::action::Action;
+use crate::components::Component;
+use crate::keymap::Key;
+
+pub struct Harness<C: Component + Send> {
+    pub component: C,
+    terminal: Terminal<TestBackend>,
+}
+
+impl<C: Component + Send> Harness<C> {
+    pub fn new(component: C) -> Self {
+        Self::with_size(component, 60, 30)
+    }
+
+    pub fn with_size(component: C, width: u16, height: u16) -> Self {
+        Harness { component, terminal: Terminal::new(TestBackend::new(width, height)).unwrap() }
+    }
+
+    /// Sends keys written like in the config, e.g. `"l"`, `"Enter"` or `"Ctrl-r"`, updating and drawing
+    /// before each one like the app does. Returns the action of the last key; every earlier key must
+    /// leave the component in charge.
+    pub async fn keys(&mut self, keys: &[&str]) -> Action {
+        let mut action = Action::None;
+        for (index, key) in keys.iter().enumerate() {
+            assert!(matches!(action, Action::None), "key {} of {keys:?} came after the component handed over", index);
+            let key: Key = key.parse().unwrap();
+            self.component.update().await.unwrap();
+            self.render();
+            action = self.component.handle_key_event(KeyEvent::from(key)).await.unwrap();
+        }
+        action
+    }
+
+    pub fn render(&mut self) -> &Buffer {
+        self.terminal.draw(|frame| self.component.render(frame, frame.size())).unwrap();
+        self.terminal.backend().buffer()
+    }
+
+    /// The rendered screen as text, one line per row without trailing spaces.
+    pub fn lines(&mut self) -> Vec<String> {
+        let buffer = self.render().clone();
+        (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer.get(x, y).symbol()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    pub fn contains(&mut self, text: &str) -> bool {
+        self.lines().iter().any(|line| line.contains(text))
+    }
+
+    /// Compares the rendered screen to `expected`, line by line, ignoring styles and trailing spaces.
+    pub fn assert_snapshot(&mut self, expected: &[&str]) {
+        let lines = self.lines();
+        assert_eq!(lines.iter().map(String::as_str).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use crate::action::Action;
+use crate::components::Component;
+use crate::config;
+
+/// The key bindings in use, shown over the current screen until any key is pressed.
+pub struct Help {
+    lines: Vec<String>,
+}
+
+#[async_trait]
+impl Component for Help {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+        Ok(Action::Pop)
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
+            return Ok(Action::Pop);
+        }
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let width = self.lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 4;
+        let area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(width),
+                Constraint::Fill(1)]
+            ).split(Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(self.lines.len() as u16 + 2),
+                Constraint::Fill(1)]
+            ).split(area)[1])[1];
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(self.lines.join("\n")).block(Block::bordered().title("Keys")), area);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+impl Help {
+    pub fn new() -> Self {
+        let keys = config::get().keys;
+        let mut lines: Vec<String> = keys.bindings().into_iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                format!("{:<20}{}", action.description(), keys.join(", "))
+            })
+            .collect();
+        if keys.numpad {
+            lines.push(format!("{:<20}1–9", "Numpad square"));
+        }
+        Help { lines }
+    }
+}
@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{List, ListState, Paragraph};
+use crate::action::Action;
+use crate::components::Component;
+use crate::components::help::Help;
+use crate::components::online_lobby::OnlineLobby;
+use crate::config;
+use crate::keymap::KeyAction;
+use crate::net::discovery::{Browser, LanGame};
+use crate::net::protocol::Settings;
+
+pub struct LanBrowser {
+    list_state: ListState,
+    browser: Option<Browser>,
+    error: Option<String>,
+}
+
+#[async_trait]
+impl Component for LanBrowser {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None)
+        }
+        match config::key_action(&key_event) {
+            Some(KeyAction::Quit) => return Ok(Action::Quit),
+            Some(KeyAction::Help) => return Ok(Action::Push(Box::new(Help::new()))),
+            Some(KeyAction::Down) => self.list_state.select_next(),
+            Some(KeyAction::Up) => self.list_state.select_previous(),
+            Some(KeyAction::Confirm) => {
+                let address = self.list_state.selected().and_then(|selected| {
+                    let games = self.browser.as_ref()?.games();
+                    games.get(selected.min(games.len().saturating_sub(1))).map(|game| game.address)
+                });
+                if let Some(address) = address {
+                    return Ok(Action::Replace(Box::new(OnlineLobby::join_address(address.to_string()))));
+                }
+            }
+            Some(KeyAction::Back) => return Ok(Action::Pop),
+            _ => ()
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        let changed = match &mut self.browser {
+            Some(browser) => browser.refresh(),
+            None if self.error.is_none() => {
+                match Browser::listen().await {
+                    Ok(browser) => self.browser = Some(browser),
+                    Err(error) => self.error = Some(format!("Cannot listen for games: {error}")),
+                }
+                true
+            }
+            None => false,
+        };
+        Ok(if changed { Action::Render } else { Action::None })
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let games: Vec<String> = self.browser.as_ref().map(|browser| browser.games().into_iter().map(describe).collect()).unwrap_or_default();
+        let status = match &self.error {
+            Some(error) => error.clone(),
+            None if games.is_empty() => "Looking for games…".to_string(),
+            None => "Enter to join, Esc to go back".to_string(),
+        };
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(games.len().max(1) as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(60),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new("LAN games").centered(), layout[1]);
+        frame.render_stateful_widget(List::new(games).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        frame.render_widget(Paragraph::new(status).centered(), layout[4]);
+    }
+}
+
+impl LanBrowser {
+    pub fn new() -> Self {
+        LanBrowser { list_state: ListState::default().with_selected(Some(0)), browser: None, error: None }
+    }
+}
+
+fn describe(game: &LanGame) -> String {
+    let board = match game.announcement.settings {
+        Settings::Normal { width, height, win_length } => format!("Normal {width}×{height}, {win_length} in a row"),
+        Settings::Super { size } => format!("Super {size}×{size}"),
+    };
+    format!("{:<12} {:<24} {}", game.announcement.name, board, game.address)
+}
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{List, ListState, Paragraph};
+use crate::action::Action;
+use crate::components::Component;
+use crate::components::help::Help;
+use crate::components::local_game::LocalGame;
+use crate::components::super_local_game::SuperLocalGame;
+use crate::config;
+use crate::keymap::KeyAction;
+use crate::save;
+use crate::save::{SavedBoard, SavedGame};
+
+pub struct LoadGame {
+    list_state: ListState,
+    saves: Vec<(PathBuf, SavedGame)>,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for LoadGame {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None)
+        }
+        match config::key_action(&key_event) {
+            Some(KeyAction::Quit) => return Ok(Action::Quit),
+            Some(KeyAction::Help) => return Ok(Action::Push(Box::new(Help::new()))),
+            Some(KeyAction::Down) => self.list_state.select_next(),
+            Some(KeyAction::Up) => self.list_state.select_previous(),
+            Some(KeyAction::Confirm) => {
+                if let Some((path, saved)) = self.selected().cloned() {
+                    match resume(path, saved) {
+                        Ok(game) => return Ok(Action::Replace(game)),
+                        Err(error) => self.status = Some(format!("Cannot load game: {error}")),
+                    }
+                }
+            }
+            Some(KeyAction::Delete) => {
+                if let Some((path, _)) = self.selected() {
+                    match save::delete(path) {
+                        Ok(()) => self.refresh(),
+                        Err(error) => self.status = Some(format!("Cannot delete game: {error}")),
+                    }
+                }
+            }
+            Some(KeyAction::Back) => return Ok(Action::Pop),
+            _ => ()
+        }
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let games: Vec<String> = self.saves.iter().map(|(_, saved)| saved.describe()).collect();
+        let status = match &self.status {
+            Some(status) => status.clone(),
+            None if games.is_empty() => "No saved games".to_string(),
+            None => "Enter to continue, Del to delete".to_string(),
+        };
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(games.len().max(1) as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(60),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new("Saved games").centered(), layout[1]);
+        frame.render_stateful_widget(List::new(games).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        frame.render_widget(Paragraph::new(status).centered(), layout[4]);
+    }
+}
+
+impl LoadGame {
+    pub fn new() -> Self {
+        let mut load_game = LoadGame { list_state: ListState::default().with_selected(Some(0)), saves: Vec::new(), status: None };
+        load_game.refresh();
+        load_game
+    }
+
+    fn refresh(&mut self) {
+        match save::list() {
+            Ok(saves) => self.saves = saves,
+            Err(error) => self.status = Some(format!("Cannot read saved games: {error}")),
+        }
+    }
+
+    fn selected(&self) -> Option<&(PathBuf, SavedGame)> {
+        let selected = self.list_state.selected()?;
+        self.saves.get(selected.min(self.saves.len().saturating_sub(1)))
+    }
+}
+
+/// The game component continuing `saved`, which was read from `path`.
+pub fn resume(path: PathBuf, saved: SavedGame) -> color_eyre::Result<Box<dyn Component + Send>> {
+    Ok(match saved.board {
+        SavedBoard::Normal { .. } => Box::new(LocalGame::load(path, saved)?),
+        SavedBoard::Super { .. } => Box::new(SuperLocalGame::load(path, saved)?),
+    })
+}
@@ -1,23 +1,36 @@
+use std::path::PathBuf;
 use async_trait::async_trait;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use ratatui::crossterm::event::KeyCode::Char;
+use color_eyre::eyre::bail;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::Rect;
 use ratatui::prelude::Text;
 use ratatui::style::{Modifier, Style};
-use ratatui::text::Span;
-use ratatui::widgets::{Clear, List, ListState, Paragraph};
+use rand::seq::SliceRandom;
+use tokio::task::JoinHandle;
 use crate::action::Action;
+use crate::ai::{minimax, Difficulty};
 use crate::components::Component;
-use crate::components::game::{Game, Square};
-use crate::components::game::Square::Draw;
-use crate::components::main_menu::MainMenu;
+use crate::components::help::Help;
+use crate::components::session;
+use crate::components::session::{Choice, MenuItem, Session, MENU};
+use crate::components::settings::Settings;
+use crate::components::game::Game;
+use crate::engine::{Position, Rules, Square, MARKS};
+use crate::engine::Square::Draw;
+use crate::config;
+use crate::event;
+use crate::keymap::KeyAction;
+use crate::notation::Record;
+use crate::save;
+use crate::save::{SavedBoard, SavedGame};
+use crate::stats::{GameResult, Variant};
 
 pub struct LocalGame {
     game: Game,
-    menu_state: ListState,
-    has_menu_open: bool,
-    in_setup: bool,
+    session: Session,
+    computer: Option<Difficulty>,
+    thinking: Option<JoinHandle<Option<(Position, Square)>>>,
 }
 
 #[async_trait]
@@ -27,141 +40,628 @@ impl Component for LocalGame {
             return Ok(Action::None);
         }
 
-        if self.in_setup {
-            match key_event.code {
-                KeyCode::Enter => {
-                    self.in_setup = false;
-                    self.game.show_selector = true;
-                }
-                Char('j') | KeyCode::Down => self.game.set_size(self.game.board.len() - 1),
-                Char('k') | KeyCode::Up => self.game.set_size(self.game.board.len() + 1),
-                _ => {}
+        match config::key_action(&key_event) {
+            Some(action) => self.act(action),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        let clicked = match self.session.point(&mouse_event) {
+            Some(clicked) => clicked,
+            None => self.thinking.is_none() && self.game.point(&mouse_event),
+        };
+        if clicked {
+            return self.act(KeyAction::Confirm);
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        self.record_result();
+        self.follow_clock();
+        if let Some(mark) = self.session.clock.as_ref().and_then(|clock| clock.expired()) {
+            self.run_out(mark)?;
+            self.follow_clock();
+            return Ok(Action::Render);
+        }
+        if self.computer_moves().await? {
+            self.follow_clock();
+            return Ok(Action::Render);
+        }
+        if self.session.clock.as_mut().is_some_and(|clock| clock.changed()) || self.game.is_animating() {
+            return Ok(Action::Render);
+        }
+        Ok(Action::None)
+    }
+
+    fn pause(&mut self) {
+        self.session.pause(self.game.board.turn(), self.game.board.moves().len());
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let winner = self.winner();
+        let name = |mark| self.player_name(mark);
+        let header = if winner == Square::None {
+            Text::from(self.session.scoreboard(&self.game.scores, self.game.board.turn(), name, self.thinking.is_some()))
+        } else if self.game.board.rules().misere && winner != Draw && self.session.out_of_time.is_none() {
+            // In wild games the completed line may be of either mark, so the loser is whoever moved last.
+            let loser = self.game.board.turn().previous(self.game.board.players());
+            Text::from(format!("{} completed a line — {} wins!", name(loser), name(winner))).style(Style::new().add_modifier(Modifier::REVERSED))
+        } else {
+            self.session.ending(winner, name)
+        };
+        let board_area = self.session.render_around(frame, area, header);
+        self.game.render(frame, board_area);
+        let settings = self.settings();
+        self.session.render_over(frame, board_area, settings);
+    }
+}
+
+impl LocalGame {
+    pub fn new() -> Self {
+        let config = config::get();
+        let mut game = Game::new();
+        game.set_size(config.defaults.width, config.defaults.height);
+        game.set_win_length(config.defaults.win_length);
+        game.set_first(config.defaults.first.mark());
+        game.show_selector = false;
+        LocalGame { game, session: Session::new(&MENU), computer: None, thinking: None }
+    }
+
+    pub fn vs_computer() -> Self {
+        let mut local_game = Self::new();
+        local_game.computer = Some(Difficulty::Medium);
+        local_game
+    }
+
+    /// Connect Four: marks drop down the columns of a 7×6 board and four in a row win.
+    pub fn gravity() -> Self {
+        let mut local_game = Self::new();
+        local_game.game.set_rules(Rules { gravity: true, ..Rules::default() });
+        local_game.game.set_size(7, 6);
+        local_game.game.set_win_length(4);
+        local_game
+    }
+
+    /// A game that skips the setup screen, as started from the command line.
+    pub fn with_settings(width: usize, height: usize, win_length: usize, computer: Option<Difficulty>) -> Self {
+        let mut local_game = Self::new();
+        local_game.game.set_size(width, height);
+        local_game.game.set_win_length(win_length);
+        local_game.game.show_selector = true;
+        local_game.computer = computer;
+        local_game.session.in_setup = false;
+        local_game
+    }
+
+    /// Handles `action`, then updates the clock right away so a move stops the mover's time.
+    fn act(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        let action = self.handle_action(action);
+        self.follow_clock();
+        action
+    }
+
+    fn handle_action(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        match action {
+            KeyAction::Quit => return Ok(self.quit()),
+            KeyAction::Help => return Ok(Action::Push(Box::new(Help::new()))),
+            _ => {}
+        }
+        match self.session.choose(action) {
+            Choice::None | Choice::Item(MenuItem::Resume) => {}
+            Choice::Start => self.game.show_selector = true,
+            Choice::Change(setting, delta) => self.change_setting(setting, delta),
+            Choice::Item(MenuItem::Undo) => self.undo(),
+            Choice::Item(MenuItem::Redo) => self.redo(),
+            Choice::Item(MenuItem::Save) => self.save(),
+            Choice::Item(MenuItem::Export) => self.export(),
+            Choice::Item(MenuItem::Rematch) => {
+                self.game.rematch();
+                self.thinking = None;
+                self.session.start_over();
             }
-        } else if self.has_menu_open {
-            match key_event.code {
-                KeyCode::Esc => {
-                    self.has_menu_open = false;
-                    self.menu_state = ListState::default().with_selected(Some(0));
-                }
-                Char('j') | KeyCode::Down => self.menu_state.select_next(),
-                Char('k') | KeyCode::Up => self.menu_state.select_previous(),
-                KeyCode::Enter => {
-                    match self.menu_state.selected().unwrap() {
-                        0 => {
-                            self.reset_menu();
-                        },
-                        1 => {
-                            self.game.rematch();
-                            self.reset_menu();
-                        }
-                        2 => {
-                            self.game.restart();
-                            self.reset_menu();
-                        }
-                        3 => return Ok(Action::ChangeComponent(Box::new(MainMenu::new()))),
-                        4 => return Ok(Action::Quit),
-
-                        _ => {}
+            Choice::Item(MenuItem::Restart) => {
+                self.game.restart();
+                self.thinking = None;
+                self.session.start_over();
+            }
+            Choice::Item(MenuItem::Settings) => return Ok(Action::Push(Box::new(Settings::new()))),
+            Choice::Item(MenuItem::Menu) => return Ok(Action::Pop),
+            Choice::Item(MenuItem::Quit) => return Ok(self.quit()),
+            Choice::Board if action == KeyAction::Undo => self.undo(),
+            Choice::Board if action == KeyAction::Redo => self.redo(),
+            Choice::Board if self.winner() == Square::None => {
+                if action == KeyAction::Back {
+                    self.session.has_menu_open = true;
+                } else if self.thinking.is_none() {
+                    self.game.act(action);
+                    if self.game.winner() != Square::None {
+                        self.session.has_menu_open = true;
                     }
                 }
-                _ => {}
             }
+            Choice::Board => self.session.has_menu_open = true,
+        }
 
-            return Ok(Action::None);
-        } else if self.game.winner.0 == Square::None {
-            if key_event.code == KeyCode::Esc {
-                self.has_menu_open = true;
-            } else {
-                self.game.handle_key_event(key_event).await?;
-                if self.game.winner.0 != Square::None {
-                    self.has_menu_open = true;
-                }
+        Ok(Action::None)
+    }
+
+    fn quit(&self) -> Action {
+        session::quit(!self.game.board.moves().is_empty() && self.winner() == Square::None)
+    }
+
+    fn winner(&self) -> Square {
+        self.session.winner(self.game.winner(), self.game.board.players())
+    }
+
+    fn player_name(&self, mark: Square) -> String {
+        if mark == Square::Circle && self.computer.is_some() { "Computer".to_string() } else { config::players().name(mark).to_string() }
+    }
+
+    /// Takes back the last move, and against the computer its reply as well so it is the player's turn again.
+    fn undo(&mut self) {
+        if self.session.out_of_time.is_some() {
+            return;
+        }
+        self.thinking = None;
+        if self.game.undo() {
+            self.session.recorded = false;
+            if self.computer.is_some() && self.game.board.turn() == Square::Circle {
+                self.game.undo();
             }
-        } else {
-            self.has_menu_open = true;
         }
+    }
 
-        Ok(Action::None)
+    fn redo(&mut self) {
+        if self.session.out_of_time.is_some() {
+            return;
+        }
+        self.thinking = None;
+        if self.game.redo() && self.computer.is_some() && self.game.board.turn() == Square::Circle {
+            self.game.redo();
+        }
     }
 
-    fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(vec![
-                Constraint::Fill(1),
-                Constraint::Length(1),
-                Constraint::Fill(1),
-                Constraint::Percentage(75),
-                Constraint::Fill(1)]
-            )
-            .split(Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Fill(1),
-                    Constraint::Fill(1)]
-                ).split(area)[1]
-            );
-        let text;
-        if self.game.winner.0 == Square::None {
-            let mut player1 = Span::from(format!("{} Player1", self.game.scores.0));
-            let mut player2 = Span::from(format!("Player2 {}", self.game.scores.1));
-            if self.game.turn == Square::X {
-                player1 = player1.style(Style::new().add_modifier(Modifier::REVERSED));
-            } else {
-                player2 = player2.style(Style::new().add_modifier(Modifier::REVERSED));
+    fn settings(&self) -> Vec<String> {
+        let [clock, on_timeout] = self.session.clock_settings();
+        vec![
+            format!("Width      {:>3}", self.game.board.width()),
+            format!("Height     {:>3}", self.game.board.height()),
+            format!("Win length {:>3}", self.game.board.win_length()),
+            clock,
+            on_timeout,
+            format!("Misère {:>9}", if self.game.board.rules().misere { "On" } else { "Off" }),
+            format!("Wild {:>11}", if self.game.board.rules().wild { "On" } else { "Off" }),
+            match self.computer {
+                Some(difficulty) => format!("Computer {:>7}", difficulty.name()),
+                None => format!("Players {:>8}", self.game.board.players()),
+            },
+        ]
+    }
+
+    fn change_setting(&mut self, setting: usize, delta: isize) {
+        match setting {
+            0 => self.game.set_size(self.game.board.width().saturating_add_signed(delta), self.game.board.height()),
+            1 => self.game.set_size(self.game.board.width(), self.game.board.height().saturating_add_signed(delta)),
+            2 => self.game.set_win_length(self.game.board.win_length().saturating_add_signed(delta)),
+            3 => self.session.step_clock(delta),
+            4 => self.session.on_timeout = self.session.on_timeout.toggle(),
+            5 => {
+                let rules = self.game.board.rules();
+                self.game.set_rules(Rules { misere: !rules.misere, ..rules });
             }
-            text = Text::from(ratatui::prelude::Line::from(vec![player1, Span::from(" | "), player2]));
-        } else {
-            if self.game.winner.0 == Draw {
-                text = Text::from("Draw!").style(Style::new().add_modifier(Modifier::REVERSED));
-            } else {
-                text = Text::from(if self.game.winner.0 == Square::X { "Player1 wins!" } else { "Player2 wins!" }).style(Style::new().add_modifier(Modifier::REVERSED));
+            6 => {
+                let rules = self.game.board.rules();
+                self.game.set_rules(Rules { wild: !rules.wild, ..rules });
             }
+            7 => match self.computer {
+                Some(difficulty) => self.computer = Some(difficulty.step(delta)),
+                None => self.game.set_players(self.game.board.players().saturating_add_signed(delta)),
+            },
+            _ => {}
+        }
+    }
+
+    /// Restores a game written by `save`, continuing to save to the same file.
+    pub fn load(path: PathBuf, saved: SavedGame) -> color_eyre::Result<Self> {
+        saved.check()?;
+        let SavedBoard::Normal { width, height, win_length, moves, computer, rules, players, marks } = saved.board else {
+            bail!("not a normal game");
+        };
+        let mut local_game = Self::new();
+        local_game.game.set_rules(rules);
+        local_game.game.set_players(players);
+        local_game.game.set_size(width, height);
+        local_game.game.set_win_length(win_length);
+        local_game.game.set_first(saved.first);
+        for (i, mv) in moves.into_iter().enumerate() {
+            match marks.get(i) {
+                Some(&mark) => local_game.game.play_mark(mv, mark)?,
+                None => local_game.game.play(mv)?,
+            };
         }
-        frame.render_widget(Paragraph::new(text).centered(), layout[1]);
-        self.game.render(frame, layout[3]);
-
-        if self.has_menu_open {
-            let menu_layout = Layout::default().direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(7),
-                    Constraint::Fill(1)]
-                ).split(Layout::default().direction(Direction::Vertical)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(5),
-                    Constraint::Fill(1)]).split(layout[3])[1]);
-            frame.render_widget(Clear::default(), menu_layout[1]);
-            frame.render_stateful_widget(List::new(["Resume", "Rematch", "Restart", "Menu", "Quit"]).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), menu_layout[1], &mut self.menu_state);
-        } else if self.in_setup {
-            let layout = Layout::default().direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(16),
-                    Constraint::Fill(1)]
-                ).split(Layout::default().direction(Direction::Vertical)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(3),
-                    Constraint::Fill(1)]).split(layout[3])[1]);
-            frame.render_widget(Clear::default(), layout[1]);
-            frame.render_widget(Paragraph::new("Select Game Size\nusing arrow keys\nand hit Enter").centered(), layout[1]);
+        local_game.game.scores = saved.scores;
+        local_game.computer = computer;
+        local_game.session.restore(path, saved.clock);
+        let over = local_game.winner() != Square::None;
+        local_game.game.show_selector = !over;
+        local_game.session.has_menu_open = over;
+        local_game.session.recorded = over;
+        Ok(local_game)
+    }
+
+    /// Has the computer search in the background when it is its turn and plays the move it
+    /// found. Returns whether it moved.
+    async fn computer_moves(&mut self) -> color_eyre::Result<bool> {
+        let Some(difficulty) = self.computer else {
+            return Ok(false);
+        };
+        if self.session.in_setup || self.session.has_menu_open || self.winner() != Square::None || self.game.board.turn() != Square::Circle || self.game.is_animating() {
+            return Ok(false);
         }
+        match self.thinking.take() {
+            None => {
+                let board = self.game.board.clone();
+                self.thinking = Some(tokio::task::spawn_blocking(move || {
+                    let mv = minimax::best_move(&board, difficulty);
+                    event::wake();
+                    mv
+                }));
+            }
+            Some(thinking) if thinking.is_finished() => {
+                if let Some((mv, mark)) = thinking.await? {
+                    self.game.play_mark(mv, mark)?;
+                    if self.game.winner() != Square::None {
+                        self.session.has_menu_open = true;
+                    }
+                    return Ok(true);
+                }
+            }
+            thinking => self.thinking = thinking,
+        }
+        Ok(false)
+    }
+
+    fn follow_clock(&mut self) {
+        let over = self.winner() != Square::None;
+        self.session.follow_clock(self.game.board.turn(), self.game.board.moves().len(), over);
+    }
+
+    /// Ends the game for `mark` whose time ran out, or plays a random move for them.
+    fn run_out(&mut self, mark: Square) -> color_eyre::Result<()> {
+        self.thinking = None;
+        if self.session.run_out(mark) {
+            let winner = self.winner();
+            self.game.scores[winner.index()] += 1;
+            self.game.show_selector = false;
+        } else if let Some(&mv) = self.game.board.legal_moves().choose(&mut rand::thread_rng()) {
+            self.game.play(mv)?;
+            if self.game.winner() != Square::None {
+                self.session.has_menu_open = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the game to the statistics once it is over.
+    fn record_result(&mut self) {
+        let winner = self.winner();
+        if !self.session.needs_recording(winner) {
+            return;
+        }
+        let board = &self.game.board;
+        let variant = Variant::Normal { width: board.width(), height: board.height(), win_length: board.win_length(), rules: board.rules(), players: board.players() };
+        let mut result = GameResult::new(variant, &self.player_name(Square::X), &self.player_name(Square::Circle), board.first(), winner, board.moves().len());
+        result.others = MARKS[2..board.players()].iter().map(|mark| self.player_name(*mark)).collect();
+        self.session.record(&result);
+    }
+
+    fn export(&mut self) {
+        if self.game.board.players() > 2 {
+            self.session.status = Some("Only games of two can be exported".to_string());
+            return;
+        }
+        if self.game.board.rules().wild {
+            self.session.status = Some("Wild games cannot be exported".to_string());
+            return;
+        }
+        let record = Record::normal(&self.game.board, &self.player_name(Square::X), &self.player_name(Square::Circle), &save::today());
+        self.session.export(&record);
+    }
+
+    fn save(&mut self) {
+        let board = SavedBoard::Normal {
+            width: self.game.board.width(),
+            height: self.game.board.height(),
+            win_length: self.game.board.win_length(),
+            moves: self.game.board.moves().to_vec(),
+            computer: self.computer,
+            rules: self.game.board.rules(),
+            players: self.game.board.players(),
+            marks: if self.game.board.rules().wild { self.game.board.moves().iter().map(|mv| self.game.board.get(*mv)).collect() } else { Vec::new() },
+        };
+        self.session.save(self.game.scores.clone(), self.game.board.first(), board);
     }
 }
 
-impl LocalGame {
-    pub fn new() -> Self {
-        let mut game = Game::new();
-        game.show_selector = false;
-        LocalGame { game, has_menu_open: false, menu_state: ListState::default().with_selected(Some(0)), in_setup: true }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::clock::{Clock, OnTimeout, TimeControl};
+    use crate::save::SavedClock;
+    use crate::components::harness::Harness;
+
+    fn harness() -> Harness<LocalGame> {
+        Harness::with_size(LocalGame::new(), 90, 30)
     }
 
-    fn reset_menu(&mut self) {
-        self.has_menu_open = false;
-        self.menu_state.select(Some(0));
+    #[tokio::test]
+    async fn setup_changes_the_board_before_the_game_starts() {
+        let mut harness = harness();
+        harness.keys(&["l", "j", "l", "l", "j", "l"]).await;
+        assert!(harness.contains("Width        4"));
+        assert!(harness.contains("Height       5"));
+        assert!(harness.contains("Win length   4"));
+
+        harness.keys(&["Enter"]).await;
+        let board = &harness.component.game.board;
+        assert_eq!((board.width(), board.height(), board.win_length()), (4, 5, 4));
+        assert!(harness.contains("0 Player1 | Player2 0"));
+        assert!(!harness.contains("Width"));
+    }
+
+    #[tokio::test]
+    async fn setup_screen_snapshot() {
+        let mut harness = Harness::with_size(LocalGame::new(), 66, 16);
+        harness.keys(&["j"]).await;
+        harness.assert_snapshot(&SETUP_SNAPSHOT);
+    }
+
+    #[tokio::test]
+    async fn menu_opens_and_closes_with_escape() {
+        let mut harness = harness();
+        harness.keys(&["Enter", "Esc"]).await;
+        assert!(harness.contains("Resume"));
+        assert!(harness.contains("Settings"));
+        harness.keys(&["Esc"]).await;
+        assert!(!harness.contains("Resume"));
+    }
+
+    #[tokio::test]
+    async fn menu_returns_to_the_main_menu() {
+        let mut harness = harness();
+        let action = harness.keys(&["Enter", "Esc", "j", "j", "j", "j", "j", "j", "j", "j", "Enter"]).await;
+        assert!(matches!(action, Action::Pop));
+    }
+
+    #[tokio::test]
+    async fn menu_opens_settings_over_the_game() {
+        let mut harness = harness();
+        let action = harness.keys(&["Enter", "Esc", "j", "j", "j", "j", "j", "j", "j", "Enter"]).await;
+        assert!(matches!(action, Action::Push(_)));
+    }
+
+    #[tokio::test]
+    async fn quitting_asks_only_with_a_game_in_progress() {
+        let mut harness = harness();
+        assert!(matches!(harness.keys(&["Enter", "q"]).await, Action::Quit));
+        assert!(matches!(harness.keys(&["Enter", "q"]).await, Action::Push(_)));
+        assert!(matches!(harness.keys(&["Esc", "j", "j", "j", "j", "j", "j", "j", "j", "j", "Enter"]).await, Action::Push(_)));
+    }
+
+    #[tokio::test]
+    async fn win_opens_the_menu_and_rematch_keeps_the_score() {
+        let mut harness = harness();
+        harness.keys(&["Enter", "Enter", "h", "Enter", "k", "Enter", "h", "k", "Enter", "j", "j", "Enter"]).await;
+        assert_eq!(harness.component.game.winner(), Square::X);
+        assert!(harness.contains("Player1 wins!"));
+        assert!(harness.contains("Rematch"));
+        harness.component.update().await.unwrap();
+        assert!(harness.component.session.recorded);
+
+        // Rematch is the sixth item.
+        harness.keys(&["j", "j", "j", "j", "j", "Enter"]).await;
+        assert!(harness.component.game.board.moves().is_empty());
+        assert!(harness.contains("1 Player1 | Player2 0"));
+    }
+
+    #[tokio::test]
+    async fn undoing_a_finished_game_records_it_again_once_finished() {
+        let mut harness = harness();
+        harness.keys(&["Enter", "Enter", "h", "Enter", "k", "Enter", "h", "k", "Enter", "j", "j", "Enter"]).await;
+        harness.component.update().await.unwrap();
+        assert!(harness.component.session.recorded);
+
+        // Undo is the second item.
+        harness.keys(&["j", "Enter"]).await;
+        assert_eq!(harness.component.winner(), Square::None);
+        assert!(!harness.component.session.recorded);
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_keys() {
+        let mut harness = harness();
+        harness.keys(&["Enter", "Enter", "l", "Enter", "u"]).await;
+        assert_eq!(harness.component.game.board.moves(), [(1, 1)]);
+        harness.keys(&["Ctrl-r"]).await;
+        assert_eq!(harness.component.game.board.moves(), [(1, 1), (2, 1)]);
+    }
+
+    #[tokio::test]
+    async fn clock_is_chosen_in_the_setup_and_shown_by_the_names() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "l", "l", "l"]).await;
+        assert!(harness.contains("Clock     30s+2s"));
+        harness.keys(&["Enter"]).await;
+        assert!(harness.contains("0 Player1 0:30 | 0:30 Player2 0"));
+    }
+
+    #[tokio::test]
+    async fn clock_stops_while_help_is_open_over_the_game() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "l", "Enter"]).await;
+        assert!(matches!(harness.keys(&["?"]).await, Action::Push(_)));
+        harness.component.pause();
+        let left = harness.component.session.clock.as_ref().unwrap().left(Square::X);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(harness.component.session.clock.as_ref().unwrap().left(Square::X), left);
+
+        harness.component.update().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(harness.component.session.clock.as_ref().unwrap().left(Square::X) < left);
+    }
+
+    #[tokio::test]
+    async fn running_out_of_time_loses_the_game() {
+        let mut harness = harness();
+        harness.keys(&["Enter", "Enter"]).await;
+        harness.component.session.clock = Some(Clock::new(TimeControl::PerMove(Duration::ZERO)));
+        assert!(matches!(harness.component.update().await.unwrap(), Action::Render));
+        assert!(harness.contains("Player2 ran out of time, Player1 wins!"));
+        assert_eq!(harness.component.game.scores, [1, 0]);
+
+        harness.keys(&["j", "j", "j", "j", "j", "Enter"]).await;
+        assert!(harness.contains("1 Player1 0.0 | 0.0 Player2 0"));
+    }
+
+    #[tokio::test]
+    async fn saved_games_keep_their_clock() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "l", "j", "l", "Enter", "Enter"]).await;
+        harness.component.save();
+        let path = harness.component.session.save_path.clone().unwrap();
+        let loaded = LocalGame::load(path.clone(), save::load(&path).unwrap()).unwrap();
+        save::delete(&path).unwrap();
+
+        let clock = loaded.session.clock.as_ref().unwrap();
+        assert_eq!(clock.control().name(), "1m");
+        assert!((Duration::from_secs(59)..=Duration::from_secs(60)).contains(&clock.left(Square::Circle)));
+        assert_eq!(loaded.session.on_timeout, OnTimeout::RandomMove);
+        assert_eq!(loaded.winner(), Square::None);
+    }
+
+    #[test]
+    fn saves_made_in_the_same_second_are_kept_apart_and_checked_on_load() {
+        let board = SavedBoard::Normal { width: 3, height: 3, win_length: 3, moves: vec![(1, 1)], computer: None, rules: Rules::default(), players: 3, marks: Vec::new() };
+        let game = SavedGame::new(vec![0, 0], Square::X, board, None);
+        let first = save::save(&game, None).unwrap();
+        let second = save::save(&game, None).unwrap();
+        assert_ne!(first, second);
+        assert!(LocalGame::load(first.clone(), save::load(&second).unwrap()).is_err());
+        save::delete(&first).unwrap();
+        save::delete(&second).unwrap();
+    }
+
+    #[test]
+    fn saves_with_marks_of_no_player_are_refused() {
+        let board = |players| SavedBoard::Normal { width: 3, height: 3, win_length: 3, moves: Vec::new(), computer: None, rules: Rules::default(), players, marks: Vec::new() };
+        assert!(LocalGame::load(PathBuf::new(), SavedGame::new(vec![0, 0], Square::None, board(2), None)).is_err());
+        assert!(LocalGame::load(PathBuf::new(), SavedGame::new(vec![0, 0], Square::Triangle, board(2), None)).is_err());
+        assert!(LocalGame::load(PathBuf::new(), SavedGame::new(vec![0, 0, 0], Square::Triangle, board(3), None)).is_ok());
+        assert!(LocalGame::load(PathBuf::new(), SavedGame::new(vec![0; 7], Square::X, board(7), None)).is_err());
+
+        let clock = SavedClock { control: TimeControl::PerMove(Duration::from_secs(5)), left: Vec::new(), on_timeout: OnTimeout::Lose, out_of_time: Some(Square::Draw) };
+        assert!(LocalGame::load(PathBuf::new(), SavedGame::new(vec![0, 0], Square::X, board(2), Some(clock))).is_err());
+    }
+
+    #[tokio::test]
+    async fn running_out_of_time_can_play_a_random_move() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "j", "l", "Enter"]).await;
+        harness.component.session.clock = Some(Clock::new(TimeControl::PerMove(Duration::ZERO)));
+        harness.component.update().await.unwrap();
+        assert_eq!(harness.component.game.board.moves().len(), 1);
+        assert_eq!(harness.component.winner(), Square::None);
+    }
+
+    #[tokio::test]
+    async fn misere_completing_a_line_loses() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "j", "j", "l", "Enter"]).await;
+        harness.keys(&["Enter", "h", "Enter", "k", "Enter", "h", "k", "Enter", "j", "j", "Enter"]).await;
+        assert_eq!(harness.component.winner(), Square::Circle);
+        assert_eq!(harness.component.game.scores, [0, 1]);
+        assert!(harness.contains("Player1 completed a line — Player2 wins!"));
+    }
+
+    #[tokio::test]
+    async fn wild_players_choose_the_mark_and_win_with_either() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "j", "j", "j", "l", "Enter"]).await;
+        assert!(harness.component.game.board.rules().wild);
+        harness.keys(&["m", "Enter"]).await;
+        assert_eq!(harness.component.game.board.get((1, 1)), Square::Circle);
+        assert_eq!(harness.component.game.board.turn(), Square::Circle);
+
+        // Player1 places an X of their own, then Player2 completes the middle column of Os.
+        for (mv, mark) in [((1, 0), Square::Circle), ((0, 0), Square::X)] {
+            harness.component.game.play_mark(mv, mark).unwrap();
+        }
+        harness.keys(&["k", "Enter"]).await;
+        assert_eq!(harness.component.game.board.get((1, 2)), Square::Circle);
+        assert_eq!(harness.component.winner(), Square::Circle);
+        assert!(harness.contains("Player2 wins!"));
+    }
+
+    #[tokio::test]
+    async fn misere_wild_loses_for_the_mover_whatever_the_mark() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "j", "j", "l", "j", "l", "Enter"]).await;
+        for (mv, mark) in [((0, 0), Square::Circle), ((2, 0), Square::X), ((0, 1), Square::Circle), ((2, 2), Square::X), ((0, 2), Square::Circle)] {
+            harness.component.game.play_mark(mv, mark).unwrap();
+        }
+        assert_eq!(harness.component.winner(), Square::Circle);
+        assert!(harness.contains("Player1 completed a line — Player2 wins!"));
+    }
+
+    #[tokio::test]
+    async fn three_players_take_turns_and_share_the_scoreboard() {
+        let mut harness = harness();
+        harness.keys(&["j", "j", "j", "j", "j", "j", "j", "l", "Enter"]).await;
+        assert!(harness.contains("0 Player1 | 0 Player2 | 0 Player3"));
+
+        for mv in [(0, 0), (0, 1), (2, 0), (1, 2), (1, 1), (2, 1), (0, 2), (1, 0)] {
+            harness.component.game.play(mv).unwrap();
+        }
+        assert_eq!(harness.component.game.board.turn(), Square::Triangle);
+        harness.keys(&["k", "l", "Enter"]).await;
+        assert_eq!(harness.component.winner(), Square::Triangle);
+        assert_eq!(harness.component.game.scores, [0, 0, 1]);
+        assert!(harness.contains("Player3 wins!"));
+    }
+
+    #[tokio::test]
+    async fn computer_answers_every_move() {
+        let mut harness = Harness::with_size(LocalGame::vs_computer(), 90, 30);
+        harness.keys(&["Enter", "Enter"]).await;
+        harness.render();
+        harness.component.update().await.unwrap();
+        assert!(harness.contains("thinking…"));
+        while !harness.component.computer_moves().await.unwrap() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(harness.component.game.board.moves().len(), 2);
+        assert_eq!(harness.component.game.board.turn(), Square::X);
     }
 
+    const SETUP_SNAPSHOT: [&str; 16] = [
+        "",
+        "                       0 Player1 | Player2 0",
+        "",
+        "                             ⡇      ⡇",
+        "                         Width        3",
+        "                         Height       3",
+        "                      ⣀⣀⣀Win length   3  ⣀⣀⣀",
+        "                         Clock        Off",
+        "                         Time out    Lose",
+        "                         Misère       Off",
+        "                      ⣀⣀⣀Wild         Off⣀⣀⣀",
+        "                         Players        2",
+        "",
+        "                          Change with ←/→",
+        "                           and hit Enter",
+        "",
+    ];
 }
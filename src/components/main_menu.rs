@@ -1,17 +1,28 @@
 use async_trait::async_trait;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use crate::action::Action;
-use crate::components::Component;
+use crate::components::{point_at_item, Component};
+use crate::components::help::Help;
 use color_eyre::Result;
-use ratatui::crossterm::event::KeyCode::Char;
 use ratatui::style::{Modifier, Style};
 use ratatui::widgets::{List, ListState, Paragraph};
 use crate::components::game_selection::GameSelection;
+use crate::components::lan_browser::LanBrowser;
+use crate::components::load_game::LoadGame;
+use crate::components::online_lobby::OnlineLobby;
+use crate::components::replay::Replay;
+use crate::components::settings::Settings;
+use crate::components::statistics::Statistics;
+use crate::config;
+use crate::keymap::KeyAction;
+
+const GAME_MODES: [&str; 9] = ["Local", "Continue", "Replays", "Statistics", "Host game", "Join game", "Find LAN games", "Settings", "Quit"];
 
 pub struct MainMenu {
     list_state: ListState,
+    list_area: Rect,
 }
 
 #[async_trait]
@@ -20,48 +31,70 @@ impl Component for MainMenu {
         if key_event.kind != KeyEventKind::Press {
             return Ok(Action::None)
         }
-        match key_event.code {
-            Char('q') => return Ok(Action::Quit),
-            Char('j') | KeyCode::Down => self.list_state.select_next(),
-            Char('k') | KeyCode::Up => self.list_state.select_previous(),
-            KeyCode::Enter => {
-                    match self.list_state.selected().unwrap() {
-                        0 => return Ok(Action::ChangeComponent(Box::new(GameSelection::new()))),
-                        1 => return Ok(Action::Quit),
-                        _ => ()
-                    }
-            }
-            _ => ()
+        match config::key_action(&key_event) {
+            Some(action) => self.act(action),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<Action> {
+        if point_at_item(&mouse_event, self.list_area, &mut self.list_state, GAME_MODES.len()) {
+            return self.act(KeyAction::Confirm);
         }
         Ok(Action::None)
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let game_modes = ["Local", "Quit"];
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
                 Constraint::Fill(1),
                 Constraint::Length(2),
-                Constraint::Length(game_modes.len() as u16),
+                Constraint::Length(GAME_MODES.len() as u16),
                 Constraint::Fill(1)]
             )
             .split(Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![
                 Constraint::Fill(1),
-                Constraint::Length(11),
+                Constraint::Length(14),
                 Constraint::Fill(1)]
             ).split(area)[1]
         );
         frame.render_widget(Paragraph::new("Tic Tac Toe"), layout[1]);
-        frame.render_stateful_widget(List::new(game_modes).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        frame.render_stateful_widget(List::new(GAME_MODES).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        self.list_area = layout[2];
     }
 }
 
 impl MainMenu {
     pub fn new() -> Self {
         let list_state = ListState::default().with_selected(Some(0));
-        MainMenu { list_state }
+        MainMenu { list_state, list_area: Rect::default() }
+    }
+
+    fn act(&mut self, action: KeyAction) -> Result<Action> {
+        match action {
+            KeyAction::Quit => return Ok(Action::Quit),
+            KeyAction::Help => return Ok(Action::Push(Box::new(Help::new()))),
+            KeyAction::Down => self.list_state.select_next(),
+            KeyAction::Up => self.list_state.select_previous(),
+            KeyAction::Confirm => {
+                match self.list_state.selected().unwrap() {
+                    0 => return Ok(Action::Push(Box::new(GameSelection::new()))),
+                    1 => return Ok(Action::Push(Box::new(LoadGame::new()))),
+                    2 => return Ok(Action::Push(Box::new(Replay::new()))),
+                    3 => return Ok(Action::Push(Box::new(Statistics::new()))),
+                    4 => return Ok(Action::Push(Box::new(OnlineLobby::host()))),
+                    5 => return Ok(Action::Push(Box::new(OnlineLobby::join()))),
+                    6 => return Ok(Action::Push(Box::new(LanBrowser::new()))),
+                    7 => return Ok(Action::Push(Box::new(Settings::new()))),
+                    8 => return Ok(Action::Quit),
+                    _ => ()
+                }
+            }
+            _ => ()
+        }
+        Ok(Action::None)
     }
 }
\ No newline at end of file
@@ -0,0 +1,498 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::prelude::Text;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::{Clear, List, ListState, Paragraph};
+use crate::action::Action;
+use crate::components::{point_at_item, Component};
+use crate::components::help::Help;
+use crate::components::game::Game;
+use crate::components::super_game::SuperGame;
+use crate::config;
+use crate::engine::{MoveError, Outcome, Position, Square};
+use crate::keymap::KeyAction;
+use crate::net::Connection;
+use crate::net::protocol::{Message, Settings};
+
+enum OnlineBoard {
+    Normal(Box<Game>),
+    Super(Box<SuperGame>),
+}
+
+impl OnlineBoard {
+    fn new(settings: Settings) -> Self {
+        match settings {
+            Settings::Normal { width, height, win_length } => {
+                let mut game = Game::new();
+                game.set_size(width, height);
+                game.set_win_length(win_length);
+                OnlineBoard::Normal(Box::new(game))
+            }
+            Settings::Super { size } => {
+                let mut game = SuperGame::new();
+                game.set_size(size);
+                OnlineBoard::Super(Box::new(game))
+            }
+        }
+    }
+
+    fn turn(&self) -> Square {
+        match self {
+            OnlineBoard::Normal(game) => game.board.turn(),
+            OnlineBoard::Super(game) => game.board.turn(),
+        }
+    }
+
+    fn winner(&self) -> Square {
+        match self {
+            OnlineBoard::Normal(game) => game.winner(),
+            OnlineBoard::Super(game) => game.managing_game.winner(),
+        }
+    }
+
+    fn scores(&mut self) -> &mut Vec<u32> {
+        match self {
+            OnlineBoard::Normal(game) => &mut game.scores,
+            OnlineBoard::Super(game) => &mut game.managing_game.scores,
+        }
+    }
+
+    /// The move under the selector as sent over the wire, `None` while a super board is still being chosen.
+    fn selected_move(&self) -> Option<(Option<Position>, Position)> {
+        match self {
+            OnlineBoard::Normal(game) => Some((None, game.selected)),
+            OnlineBoard::Super(game) => game.selected_move().map(|(board, square)| (Some(board), square)),
+        }
+    }
+
+    fn play(&mut self, board: Option<Position>, square: Position) -> Result<Outcome, MoveError> {
+        match (self, board) {
+            (OnlineBoard::Normal(game), None) => game.play(square),
+            (OnlineBoard::Super(game), Some(board)) => game.play((board, square)),
+            _ => Err(MoveError::WrongBoard),
+        }
+    }
+
+    /// Moves the selector to what the mouse is over. Returns whether it was clicked.
+    fn point(&mut self, mouse_event: &MouseEvent) -> bool {
+        match self {
+            OnlineBoard::Normal(game) => game.point(mouse_event),
+            OnlineBoard::Super(game) => game.point(mouse_event),
+        }
+    }
+
+    /// Moves the selector. Choosing a super board is local, playing a square is up to the caller.
+    fn act(&mut self, action: KeyAction) {
+        match self {
+            OnlineBoard::Normal(game) => game.act(action),
+            OnlineBoard::Super(game) => game.act(action),
+        }
+    }
+
+    /// Moves the selector to the numeric keypad cell. Returns whether that picked a square to play.
+    fn select_cell(&mut self, cell: Position) -> bool {
+        match self {
+            OnlineBoard::Normal(game) if game.is_numpad_sized() => {
+                game.selected = cell;
+                true
+            }
+            OnlineBoard::Super(game) if game.board.size() == 3 => match game.selected_move() {
+                Some((board, _)) => {
+                    game.games[board.0][board.1].selected = cell;
+                    true
+                }
+                None => {
+                    game.act(KeyAction::Cell(cell));
+                    false
+                }
+            },
+            _ => false,
+        }
+    }
+
+    fn rematch(&mut self) {
+        match self {
+            OnlineBoard::Normal(game) => game.rematch(),
+            OnlineBoard::Super(game) => game.rematch(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+enum MenuItem {
+    Resume,
+    Resign,
+    OfferRematch,
+    AcceptRematch,
+    Leave,
+    Quit,
+}
+
+impl MenuItem {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Resume => "Resume",
+            MenuItem::Resign => "Resign",
+            MenuItem::OfferRematch => "Offer rematch",
+            MenuItem::AcceptRematch => "Accept rematch",
+            MenuItem::Leave => "Leave",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+pub struct OnlineGame {
+    connection: Connection,
+    board: OnlineBoard,
+    mark: Square,
+    menu_state: ListState,
+    menu_area: Rect,
+    has_menu_open: bool,
+    connected: bool,
+    resigned: Option<Square>,
+    rematch_offered: bool,
+    rematch_requested: bool,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for OnlineGame {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+
+        match config::key_action(&key_event) {
+            Some(action) => self.act(action),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        let clicked = if self.has_menu_open {
+            let items = self.menu_items().len();
+            point_at_item(&mouse_event, self.menu_area, &mut self.menu_state, items)
+        } else if self.connected && !self.is_over() && self.board.turn() == self.mark {
+            self.board.point(&mouse_event)
+        } else {
+            false
+        };
+        if clicked {
+            return self.act(KeyAction::Confirm);
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        let mut changed = false;
+        while let Some(message) = self.connection.try_recv() {
+            self.receive(message);
+            changed = true;
+        }
+        if let OnlineBoard::Normal(game) = &mut self.board {
+            game.show_selector = game.winner() == Square::None && game.board.turn() == self.mark;
+        }
+        Ok(if changed { Action::Render } else { Action::None })
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Percentage(75),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Fill(1),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        let reversed = Style::new().add_modifier(Modifier::REVERSED);
+        let text = if let Some(resigned) = self.resigned {
+            Text::from(if resigned == self.mark { "You resigned" } else { "Opponent resigned, you win!" }).style(reversed)
+        } else if self.board.winner() == Square::Draw {
+            Text::from("Draw!").style(reversed)
+        } else if self.board.winner() != Square::None {
+            Text::from(if self.board.winner() == self.mark { "You win!" } else { "Opponent wins!" }).style(reversed)
+        } else {
+            let scores = self.board.scores().clone();
+            let (name1, name2) = if self.mark == Square::X { ("You", "Opponent") } else { ("Opponent", "You") };
+            let mut player1 = Span::from(format!("{} {}", scores[0], name1));
+            let mut player2 = Span::from(format!("{} {}", name2, scores[1]));
+            if self.board.turn() == Square::X {
+                player1 = player1.style(reversed);
+            } else {
+                player2 = player2.style(reversed);
+            }
+            Text::from(ratatui::prelude::Line::from(vec![player1, Span::from(" | "), player2]))
+        };
+        frame.render_widget(Paragraph::new(text).centered(), layout[1]);
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).centered(), layout[4]);
+        }
+        match &mut self.board {
+            OnlineBoard::Normal(game) => game.render(frame, layout[3]),
+            OnlineBoard::Super(game) => game.render(frame, layout[3]),
+        }
+
+        if self.has_menu_open {
+            let items: Vec<&str> = self.menu_items().iter().map(MenuItem::label).collect();
+            let menu_layout = Layout::default().direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(14),
+                    Constraint::Fill(1)]
+                ).split(Layout::default().direction(Direction::Vertical)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(items.len() as u16),
+                    Constraint::Fill(1)]).split(layout[3])[1]);
+            frame.render_widget(Clear, menu_layout[1]);
+            frame.render_stateful_widget(List::new(items).highlight_style(reversed), menu_layout[1], &mut self.menu_state);
+            self.menu_area = menu_layout[1];
+        }
+    }
+}
+
+impl OnlineGame {
+    pub fn new(connection: Connection, settings: Settings, mark: Square) -> Self {
+        OnlineGame {
+            connection,
+            board: OnlineBoard::new(settings),
+            mark,
+            menu_state: ListState::default().with_selected(Some(0)),
+            menu_area: Rect::default(),
+            has_menu_open: false,
+            connected: true,
+            resigned: None,
+            rematch_offered: false,
+            rematch_requested: false,
+            status: None,
+        }
+    }
+
+    fn act(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        match action {
+            KeyAction::Quit => {
+                self.connection.send(Message::Disconnect);
+                return Ok(Action::Quit);
+            }
+            KeyAction::Help => return Ok(Action::Push(Box::new(Help::new()))),
+            _ => {}
+        }
+        if self.has_menu_open {
+            let items = self.menu_items();
+            match action {
+                KeyAction::Back => self.reset_menu(),
+                KeyAction::Down => self.menu_state.select_next(),
+                KeyAction::Up => self.menu_state.select_previous(),
+                KeyAction::Confirm => {
+                    match items[self.menu_state.selected().unwrap().min(items.len() - 1)] {
+                        MenuItem::Resume => self.reset_menu(),
+                        MenuItem::Resign => {
+                            self.connection.send(Message::Resign);
+                            self.resign(self.mark);
+                            self.reset_menu();
+                        }
+                        MenuItem::OfferRematch => {
+                            self.connection.send(Message::RematchOffer);
+                            self.rematch_offered = true;
+                            self.status = Some("Rematch offered".to_string());
+                            self.reset_menu();
+                        }
+                        MenuItem::AcceptRematch => {
+                            self.connection.send(Message::RematchAccept);
+                            self.rematch();
+                            self.reset_menu();
+                        }
+                        MenuItem::Leave => {
+                            self.connection.send(Message::Disconnect);
+                            return Ok(Action::Pop);
+                        }
+                        MenuItem::Quit => {
+                            self.connection.send(Message::Disconnect);
+                            return Ok(Action::Quit);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return Ok(Action::None);
+        }
+
+        if action == KeyAction::Back || self.is_over() {
+            self.has_menu_open = true;
+        } else if self.connected && self.board.turn() == self.mark {
+            let action = match action {
+                KeyAction::Cell(cell) if self.board.select_cell(cell) => KeyAction::Confirm,
+                action => action,
+            };
+            match self.board.selected_move() {
+                Some((board, square)) if action == KeyAction::Confirm => {
+                    if self.board.play(board, square).is_ok() {
+                        self.connection.send(Message::Move { board, square });
+                        self.has_menu_open = self.is_over();
+                    }
+                }
+                _ => self.board.act(action),
+            }
+        }
+        Ok(Action::None)
+    }
+
+    fn is_over(&self) -> bool {
+        self.resigned.is_some() || self.board.winner() != Square::None
+    }
+
+    fn menu_items(&self) -> Vec<MenuItem> {
+        let mut items = vec![MenuItem::Resume];
+        if self.connected {
+            if !self.is_over() {
+                items.push(MenuItem::Resign);
+            } else if self.rematch_requested {
+                items.push(MenuItem::AcceptRematch);
+            } else if !self.rematch_offered {
+                items.push(MenuItem::OfferRematch);
+            }
+        }
+        items.extend([MenuItem::Leave, MenuItem::Quit]);
+        items
+    }
+
+    fn reset_menu(&mut self) {
+        self.has_menu_open = false;
+        self.menu_state.select(Some(0));
+    }
+
+    fn receive(&mut self, message: Message) {
+        if !self.connected {
+            return;
+        }
+        match message {
+            Message::Move { board, square } => {
+                let result = if self.is_over() || self.board.turn() == self.mark {
+                    Err(MoveError::GameOver.to_string())
+                } else {
+                    self.board.play(board, square).map_err(|error| error.to_string())
+                };
+                match result {
+                    Ok(_) => self.has_menu_open = self.is_over(),
+                    Err(reason) => self.fail(format!("Opponent sent an illegal move: {reason}")),
+                }
+            }
+            Message::Resign if !self.is_over() => {
+                self.resign(self.mark.opponent());
+                self.has_menu_open = true;
+            }
+            // Crossed the move that ended the game, or our own resignation, so there is nothing to resign.
+            Message::Resign => {}
+            Message::RematchOffer if self.is_over() => {
+                self.rematch_requested = true;
+                self.status = Some("Opponent offers a rematch".to_string());
+            }
+            Message::RematchAccept if self.rematch_offered => self.rematch(),
+            Message::Error { reason } => {
+                self.connected = false;
+                self.status = Some(format!("Connection closed: {reason}"));
+            }
+            Message::Disconnect => {
+                self.connected = false;
+                self.status = Some("Opponent left".to_string());
+                self.has_menu_open = true;
+            }
+            other => self.fail(format!("Unexpected message from opponent: {other:?}")),
+        }
+    }
+
+    /// Reports a protocol violation to the opponent and drops the connection.
+    fn fail(&mut self, reason: String) {
+        self.connection.send(Message::Error { reason: reason.clone() });
+        self.connected = false;
+        self.status = Some(reason);
+        self.has_menu_open = true;
+    }
+
+    fn resign(&mut self, loser: Square) {
+        self.resigned = Some(loser);
+        self.board.scores()[loser.opponent().index()] += 1;
+    }
+
+    fn rematch(&mut self) {
+        self.board.rematch();
+        self.resigned = None;
+        self.rematch_offered = false;
+        self.rematch_requested = false;
+        self.status = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+
+    /// A game played as O against the other end of a localhost connection.
+    async fn joined() -> (Harness<OnlineGame>, Connection) {
+        let (host, joiner) = Connection::pair(Settings::default()).await;
+        (Harness::with_size(OnlineGame::new(joiner, Settings::default(), Square::Circle), 90, 30), host)
+    }
+
+    /// Updates the game until `done` holds, as ticks would.
+    async fn update_until(harness: &mut Harness<OnlineGame>, done: impl Fn(&OnlineGame) -> bool) {
+        for _ in 0..200 {
+            harness.component.update().await.unwrap();
+            if done(&harness.component) {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        panic!("the game never got there");
+    }
+
+    fn moves(game: &OnlineGame) -> usize {
+        match &game.board {
+            OnlineBoard::Normal(game) => game.board.moves().len(),
+            OnlineBoard::Super(game) => game.board.moves().len(),
+        }
+    }
+
+    #[tokio::test]
+    async fn moves_are_exchanged_and_illegal_ones_drop_the_connection() {
+        let (mut harness, mut host) = joined().await;
+        host.send(Message::Move { board: None, square: (1, 1) });
+        update_until(&mut harness, |game| moves(game) == 1).await;
+        assert_eq!(harness.component.board.turn(), Square::Circle);
+
+        harness.keys(&["h", "Enter"]).await;
+        assert_eq!(host.recv().await, Message::Move { board: None, square: (0, 1) });
+
+        host.send(Message::Move { board: None, square: (0, 1) });
+        update_until(&mut harness, |game| !game.connected).await;
+        assert_eq!(moves(&harness.component), 2);
+        assert!(matches!(host.recv().await, Message::Error { reason } if reason.contains("illegal move")));
+        assert!(harness.contains("Opponent sent an illegal move"));
+    }
+
+    #[tokio::test]
+    async fn resignations_that_cross_are_ignored() {
+        let (mut harness, mut host) = joined().await;
+        // Resign is the second item of the menu.
+        harness.keys(&["Esc", "j", "Enter"]).await;
+        assert_eq!(host.recv().await, Message::Resign);
+
+        host.send(Message::Resign);
+        host.send(Message::RematchOffer);
+        update_until(&mut harness, |game| game.rematch_requested).await;
+        assert!(harness.component.connected);
+        assert_eq!(harness.component.resigned, Some(Square::Circle));
+    }
+}
@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{List, ListState, Paragraph};
+use tokio::task::JoinHandle;
+use crate::action::Action;
+use crate::components::Component;
+use crate::config;
+use crate::config::Mode;
+use crate::components::online_game::OnlineGame;
+use crate::engine::Square;
+use crate::event;
+use crate::keymap;
+use crate::keymap::KeyAction;
+use crate::net::Connection;
+use crate::net::discovery;
+use crate::net::discovery::Announcement;
+use crate::net::protocol::{Settings, MAX_SIZE, PROTOCOL_VERSION};
+
+pub struct OnlineLobby {
+    hosting: bool,
+    settings: Settings,
+    address: String,
+    /// Whether keys type into the address, which comes after the settings when hosting.
+    typing: bool,
+    setup_state: ListState,
+    /// Hands the game over to the app itself once connected, only failures are left to report.
+    connecting: Option<JoinHandle<color_eyre::Result<()>>>,
+    announcing: Option<JoinHandle<()>>,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for OnlineLobby {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+        let action = config::key_action(&key_event);
+        if self.connecting.is_some() {
+            match action {
+                Some(KeyAction::Back) => self.cancel(),
+                Some(KeyAction::Quit) => return Ok(Action::Quit),
+                _ => {}
+            }
+            return Ok(Action::None);
+        }
+
+        if self.typing {
+            if let Some(char) = keymap::typed_char(&key_event) {
+                self.address.push(char);
+                return Ok(Action::None);
+            }
+            if key_event.code == KeyCode::Backspace {
+                self.address.pop();
+                return Ok(Action::None);
+            }
+        }
+        match action {
+            Some(KeyAction::Back) => return Ok(Action::Pop),
+            Some(KeyAction::Quit) => return Ok(Action::Quit),
+            Some(KeyAction::Down) => self.move_down(),
+            Some(KeyAction::Up) => self.move_up(),
+            Some(KeyAction::Left) => self.change_setting(-1),
+            Some(KeyAction::Right) => self.change_setting(1),
+            Some(KeyAction::Confirm) => self.connect(),
+            _ => {}
+        }
+        Ok(Action::None)
+    }
+
+    fn handle_paste(&mut self, pasted_string: String) -> color_eyre::Result<Action> {
+        if self.connecting.is_none() {
+            self.address.push_str(pasted_string.trim());
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        if !self.connecting.as_ref().is_some_and(JoinHandle::is_finished) {
+            return Ok(Action::None);
+        }
+        self.stop_announcing();
+        if let Err(error) = self.connecting.take().unwrap().await? {
+            self.status = Some(format!("Failed: {error}"));
+        }
+        Ok(Action::Render)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let settings = self.setting_lines();
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(settings.len() as u16),
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(40),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new(if self.hosting { "Host game" } else { "Join game" }).centered(), layout[1]);
+        frame.render_stateful_widget(List::new(settings).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.setup_state);
+        frame.render_widget(Paragraph::new(format!("Address {}{}", self.address, if self.typing { "_" } else { "" })), layout[4]);
+        let help = if self.connecting.is_some() {
+            "Esc to cancel"
+        } else if self.typing {
+            "Type the address and hit Enter"
+        } else {
+            "Change with ←/→, ↓ to the address"
+        };
+        frame.render_widget(Paragraph::new(help).centered(), layout[6]);
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).centered(), layout[5]);
+        }
+    }
+}
+
+impl OnlineLobby {
+    pub fn host() -> Self {
+        let defaults = config::get().defaults;
+        // The defaults may be larger than an online board can be.
+        let (width, height) = (defaults.width.min(MAX_SIZE), defaults.height.min(MAX_SIZE));
+        let settings = match defaults.mode {
+            Mode::Normal => Settings::Normal { width, height, win_length: defaults.win_length.min(width.max(height)) },
+            Mode::Super => Settings::Super { size: defaults.super_size.min(MAX_SIZE) },
+        };
+        OnlineLobby { hosting: true, settings, address: "0.0.0.0:7000".to_string(), typing: false, setup_state: ListState::default().with_selected(Some(0)), connecting: None, announcing: None, status: None }
+    }
+
+    pub fn join() -> Self {
+        OnlineLobby { hosting: false, settings: Settings::default(), address: "127.0.0.1:7000".to_string(), typing: true, setup_state: ListState::default(), connecting: None, announcing: None, status: None }
+    }
+
+    /// Starts waiting for an opponent on `address` right away.
+    pub fn host_address(address: String, settings: Settings) -> Self {
+        let mut lobby = Self::host();
+        lobby.address = address;
+        lobby.settings = settings;
+        lobby.connect();
+        lobby
+    }
+
+    /// Starts connecting to `address` right away, e.g. for a game found on the LAN.
+    pub fn join_address(address: String) -> Self {
+        let mut lobby = Self::join();
+        lobby.address = address;
+        lobby.connect();
+        lobby
+    }
+
+    fn setting_lines(&self) -> Vec<String> {
+        if !self.hosting {
+            return Vec::new();
+        }
+        match self.settings {
+            Settings::Normal { width, height, win_length } => vec![
+                format!("Mode       {:>6}", "Normal"),
+                format!("Width      {:>6}", width),
+                format!("Height     {:>6}", height),
+                format!("Win length {:>6}", win_length),
+            ],
+            Settings::Super { size } => vec![
+                format!("Mode       {:>6}", "Super"),
+                format!("Size       {:>6}", size),
+            ],
+        }
+    }
+
+    /// Moves to the next setting, or past the last one to the address.
+    fn move_down(&mut self) {
+        match self.setup_state.selected() {
+            Some(selected) if selected + 1 < self.setting_lines().len() => self.setup_state.select_next(),
+            Some(_) => {
+                self.setup_state.select(None);
+                self.typing = true;
+            }
+            None => {}
+        }
+    }
+
+    /// Moves to the previous setting, from the address to the last one.
+    fn move_up(&mut self) {
+        if !self.hosting {
+            return;
+        }
+        if self.typing {
+            self.typing = false;
+            self.setup_state.select(Some(self.setting_lines().len() - 1));
+        } else {
+            self.setup_state.select_previous();
+        }
+    }
+
+    fn change_setting(&mut self, delta: isize) {
+        let Some(selected) = self.setup_state.selected() else {
+            return;
+        };
+        let change = |value: usize, min: usize, max: usize| value.saturating_add_signed(delta).clamp(min, max);
+        self.settings = match (self.settings, selected) {
+            (Settings::Normal { .. }, 0) => Settings::Super { size: 3 },
+            (Settings::Super { .. }, 0) => Settings::default(),
+            (Settings::Normal { width, height, win_length }, 1) => Settings::Normal { width: change(width, 3, MAX_SIZE), height, win_length },
+            (Settings::Normal { width, height, win_length }, 2) => Settings::Normal { width, height: change(height, 3, MAX_SIZE), win_length },
+            (Settings::Normal { width, height, win_length }, 3) => Settings::Normal { width, height, win_length: change(win_length, 3, width.max(height)) },
+            (Settings::Super { size }, 1) => Settings::Super { size: change(size, 3, MAX_SIZE) },
+            (settings, _) => settings,
+        };
+        if let Settings::Normal { width, height, win_length } = &mut self.settings {
+            *win_length = (*win_length).min((*width).max(*height));
+        }
+    }
+
+    fn connect(&mut self) {
+        let address = self.address.clone();
+        if self.hosting {
+            let settings = self.settings;
+            self.status = Some(format!("Waiting for an opponent on {address}…"));
+            if let Some(port) = address.rsplit(':').next().and_then(|port| port.parse().ok()) {
+                let name = config::get().players.x;
+                self.announcing = Some(discovery::announce(Announcement { version: PROTOCOL_VERSION, name, port, settings }));
+            }
+            self.connecting = Some(tokio::spawn(async move {
+                let connection = Connection::host(&address, settings).await.inspect_err(|_| event::wake())?;
+                event::send(Action::Replace(Box::new(OnlineGame::new(connection, settings, Square::X))));
+                Ok(())
+            }));
+        } else {
+            self.status = Some(format!("Connecting to {address}…"));
+            self.connecting = Some(tokio::spawn(async move {
+                let (connection, settings) = Connection::join(&address).await.inspect_err(|_| event::wake())?;
+                event::send(Action::Replace(Box::new(OnlineGame::new(connection, settings, Square::Circle))));
+                Ok(())
+            }));
+        }
+    }
+
+    fn cancel(&mut self) {
+        if let Some(connecting) = self.connecting.take() {
+            connecting.abort();
+        }
+        self.stop_announcing();
+        self.status = None;
+    }
+
+    fn stop_announcing(&mut self) {
+        if let Some(announcing) = self.announcing.take() {
+            announcing.abort();
+        }
+    }
+}
+
+impl Drop for OnlineLobby {
+    fn drop(&mut self) {
+        self.stop_announcing();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+
+    #[tokio::test]
+    async fn hosting_types_into_the_address_only_below_the_settings() {
+        let mut harness = Harness::new(OnlineLobby::host());
+        harness.keys(&["j", "l", "l", "h", "k", "j", "?"]).await;
+        assert_eq!(harness.component.address, "0.0.0.0:7000");
+        assert!(harness.contains("Width           4"));
+        assert!(harness.contains("Address 0.0.0.0:7000"));
+        assert!(!harness.contains("Address 0.0.0.0:7000_"));
+
+        harness.keys(&["j", "j", "j", "Backspace", "1", "j", "k"]).await;
+        assert_eq!(harness.component.address, "0.0.0.0:7001jk");
+        assert!(harness.contains("Address 0.0.0.0:7001jk_"));
+
+        harness.keys(&["Up", "l"]).await;
+        assert!(!harness.component.typing);
+        assert!(harness.contains("Win length      4"));
+    }
+
+    #[tokio::test]
+    async fn joining_types_into_the_address_right_away() {
+        let mut harness = Harness::new(OnlineLobby::join());
+        harness.keys(&["Backspace", "Backspace", "Backspace", "Backspace", "8", "0", "h"]).await;
+        assert_eq!(harness.component.address, "127.0.0.1:80h");
+    }
+}
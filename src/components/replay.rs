@@ -0,0 +1,318 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{List, ListState, Paragraph};
+use crate::action::Action;
+use crate::components::Component;
+use crate::components::help::Help;
+use crate::components::game::Game;
+use crate::components::super_game::SuperGame;
+use crate::config;
+use crate::keymap::KeyAction;
+use crate::notation::{ParseError, Record, RecordedMoves};
+use crate::save;
+
+const SPEEDS: [Duration; 5] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+];
+
+enum ReplayBoard {
+    Normal(Box<Game>),
+    Super(Box<SuperGame>),
+}
+
+impl ReplayBoard {
+    /// Every move of `record` played and taken back again, so that redoing steps through the game.
+    fn new(record: &Record) -> Self {
+        let mut board = match &record.moves {
+            RecordedMoves::Normal { width, height, win_length, rules, moves } => {
+                let mut game = Game::new();
+                game.set_rules(*rules);
+                game.set_size(*width, *height);
+                game.set_win_length(*win_length);
+                game.set_first(record.first);
+                for &mv in moves {
+                    let _ = game.play(mv);
+                }
+                ReplayBoard::Normal(Box::new(game))
+            }
+            RecordedMoves::Super { size, moves } => {
+                let mut game = SuperGame::new();
+                game.set_size(*size);
+                game.set_first(record.first);
+                for &mv in moves {
+                    let _ = game.play(mv);
+                }
+                ReplayBoard::Super(Box::new(game))
+            }
+        };
+        while board.back() {}
+        board
+    }
+
+    fn back(&mut self) -> bool {
+        let moved = match self {
+            ReplayBoard::Normal(game) => game.undo(),
+            ReplayBoard::Super(game) => game.undo(),
+        };
+        self.mark_last_move();
+        moved
+    }
+
+    fn forward(&mut self) -> bool {
+        let moved = match self {
+            ReplayBoard::Normal(game) => game.redo(),
+            ReplayBoard::Super(game) => game.redo(),
+        };
+        self.mark_last_move();
+        moved
+    }
+
+    /// Points the selector at the move just played instead of where a player would continue.
+    fn mark_last_move(&mut self) {
+        match self {
+            ReplayBoard::Normal(game) => {
+                game.show_selector = false;
+                if let Some(&mv) = game.board.moves().last() {
+                    game.selected = mv;
+                    game.show_selector = true;
+                }
+            }
+            ReplayBoard::Super(game) => {
+                game.managing_game.show_selector = false;
+                for sub_game in game.games.iter_mut().flatten() {
+                    sub_game.show_selector = false;
+                }
+                if let Some(&(board, square)) = game.board.moves().last() {
+                    let sub_game = &mut game.games[board.0][board.1];
+                    sub_game.selected = square;
+                    sub_game.show_selector = true;
+                }
+            }
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        matches!(self, ReplayBoard::Normal(game) if game.is_animating())
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        match self {
+            ReplayBoard::Normal(game) => game.render(frame, area),
+            ReplayBoard::Super(game) => game.render(frame, area),
+        }
+    }
+}
+
+struct Viewer {
+    record: Record,
+    board: ReplayBoard,
+    shown: usize,
+    playing: bool,
+    speed: usize,
+    last_step: Instant,
+}
+
+impl Viewer {
+    fn new(record: Record) -> Self {
+        let board = ReplayBoard::new(&record);
+        Viewer { record, board, shown: 0, playing: false, speed: 2, last_step: Instant::now() }
+    }
+
+    fn forward(&mut self) {
+        if self.board.forward() {
+            self.shown += 1;
+        }
+        self.last_step = Instant::now();
+    }
+
+    fn back(&mut self) {
+        if self.board.back() {
+            self.shown -= 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.shown == self.record.move_names().len()
+    }
+}
+
+pub struct Replay {
+    list_state: ListState,
+    records: Vec<(PathBuf, Result<Record, ParseError>)>,
+    viewer: Option<Viewer>,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for Replay {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None)
+        }
+        let Some(action) = config::key_action(&key_event) else {
+            return Ok(Action::None);
+        };
+        if action == KeyAction::Help {
+            return Ok(Action::Push(Box::new(Help::new())));
+        }
+        if let Some(viewer) = &mut self.viewer {
+            match action {
+                KeyAction::Quit => return Ok(Action::Quit),
+                KeyAction::Right => viewer.forward(),
+                KeyAction::Left => viewer.back(),
+                KeyAction::First => while viewer.board.back() { viewer.shown -= 1 },
+                KeyAction::Last => while viewer.board.forward() { viewer.shown += 1 },
+                KeyAction::Play => {
+                    viewer.playing = !viewer.playing && !viewer.at_end();
+                    viewer.last_step = Instant::now();
+                }
+                KeyAction::Faster => viewer.speed = viewer.speed.saturating_sub(1),
+                KeyAction::Slower => viewer.speed = (viewer.speed + 1).min(SPEEDS.len() - 1),
+                KeyAction::Back => self.viewer = None,
+                _ => ()
+            }
+            return Ok(Action::None);
+        }
+        match action {
+            KeyAction::Quit => return Ok(Action::Quit),
+            KeyAction::Down => self.list_state.select_next(),
+            KeyAction::Up => self.list_state.select_previous(),
+            KeyAction::Confirm => {
+                let selected = self.list_state.selected().and_then(|selected| {
+                    self.records.get(selected.min(self.records.len().saturating_sub(1)))
+                });
+                match selected {
+                    Some((_, Ok(record))) => self.viewer = Some(Viewer::new(record.clone())),
+                    Some((path, Err(error))) => self.status = Some(format!("{}: {error}", file_name(path))),
+                    None => {}
+                }
+            }
+            KeyAction::Back => return Ok(Action::Pop),
+            _ => ()
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        if let Some(viewer) = &mut self.viewer {
+            if viewer.playing && viewer.last_step.elapsed() >= SPEEDS[viewer.speed] {
+                viewer.forward();
+                viewer.playing = !viewer.at_end();
+                return Ok(Action::Render);
+            }
+            if viewer.board.is_animating() {
+                return Ok(Action::Render);
+            }
+        }
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        match &mut self.viewer {
+            Some(viewer) => Self::render_viewer(viewer, frame, area),
+            None => self.render_list(frame, area),
+        }
+    }
+}
+
+impl Replay {
+    pub fn new() -> Self {
+        let mut replay = Replay { list_state: ListState::default().with_selected(Some(0)), records: Vec::new(), viewer: None, status: None };
+        match save::records() {
+            Ok(records) => replay.records = records,
+            Err(error) => replay.status = Some(format!("Cannot read records: {error}")),
+        }
+        replay
+    }
+
+    fn render_list(&mut self, frame: &mut Frame, area: Rect) {
+        let records: Vec<String> = self.records.iter().map(|(path, record)| match record {
+            Ok(record) => format!("{}  {} vs {}  {}", record.date, record.x, record.o, record.result_name()),
+            Err(_) => format!("{}  unreadable", file_name(path)),
+        }).collect();
+        let status = match &self.status {
+            Some(status) => status.clone(),
+            None if records.is_empty() => "No records, export a finished game first".to_string(),
+            None => "Enter to watch, Esc to go back".to_string(),
+        };
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(records.len().max(1) as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(60),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new("Replays").centered(), layout[1]);
+        frame.render_stateful_widget(List::new(records).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        frame.render_widget(Paragraph::new(status).centered(), layout[4]);
+    }
+
+    fn render_viewer(viewer: &mut Viewer, frame: &mut Frame, area: Rect) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1)]
+            ).split(area);
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Percentage(75),
+                Constraint::Fill(1)]
+            );
+        let board_layout = layout.split(columns[1]);
+        let list_layout = layout.split(columns[2]);
+
+        let record = &viewer.record;
+        let header = format!("{} vs {}  {}", record.x, record.o, record.result_name());
+        frame.render_widget(Paragraph::new(header).centered(), board_layout[1]);
+        viewer.board.render(frame, board_layout[3]);
+
+        let names = record.move_names();
+        let moves: Vec<String> = names.chunks(2).enumerate()
+            .map(|(index, pair)| format!("{:>3}. {:<7} {}", index + 1, pair[0], pair.get(1).map_or("", String::as_str)))
+            .collect();
+        let mut list_state = ListState::default().with_selected(viewer.shown.checked_sub(1).map(|played| played / 2));
+        frame.render_stateful_widget(
+            List::new(moves).highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list_layout[3].inner(Margin::new(2, 0)),
+            &mut list_state,
+        );
+
+        let playback = if viewer.playing {
+            format!("playing, one move every {:.2}s", SPEEDS[viewer.speed].as_secs_f64())
+        } else {
+            "paused".to_string()
+        };
+        let status = format!("Move {}/{}, {playback}\n←/→ step, Space play, +/- speed", viewer.shown, names.len());
+        frame.render_widget(Paragraph::new(status).centered(), board_layout[4]);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
@@ -0,0 +1,398 @@
+use std::path::PathBuf;
+use ratatui::crossterm::event::MouseEvent;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::prelude::{Line, Text};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::{Clear, List, ListState, Paragraph};
+use crate::action::Action;
+use crate::clock::{Clock, OnTimeout, TimeControl};
+use crate::components::confirm::Confirm;
+use crate::components::point_at_item;
+use crate::engine::{Square, MARKS};
+use crate::keymap::KeyAction;
+use crate::notation::Record;
+use crate::save;
+use crate::save::{SavedBoard, SavedClock, SavedGame};
+use crate::stats;
+use crate::stats::GameResult;
+
+/// The items of the menu opened over a local game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuItem {
+    Resume,
+    Undo,
+    Redo,
+    Save,
+    Export,
+    Rematch,
+    Restart,
+    Settings,
+    Menu,
+    Quit,
+}
+
+impl MenuItem {
+    fn name(self) -> &'static str {
+        match self {
+            MenuItem::Resume => "Resume",
+            MenuItem::Undo => "Undo",
+            MenuItem::Redo => "Redo",
+            MenuItem::Save => "Save",
+            MenuItem::Export => "Export",
+            MenuItem::Rematch => "Rematch",
+            MenuItem::Restart => "Restart",
+            MenuItem::Settings => "Settings",
+            MenuItem::Menu => "Menu",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+/// The menu of the games that can be saved and exported.
+pub const MENU: [MenuItem; 10] = [
+    MenuItem::Resume,
+    MenuItem::Undo,
+    MenuItem::Redo,
+    MenuItem::Save,
+    MenuItem::Export,
+    MenuItem::Rematch,
+    MenuItem::Restart,
+    MenuItem::Settings,
+    MenuItem::Menu,
+    MenuItem::Quit,
+];
+
+/// What a key chose in the setup or the menu, for the game to carry out.
+#[derive(PartialEq, Eq, Debug)]
+pub enum Choice {
+    /// Nothing is left for the game to do.
+    None,
+    /// The setup was confirmed and the game begins.
+    Start,
+    /// The setting at the index goes a step down or up.
+    Change(usize, isize),
+    /// An item was picked from the menu.
+    Item(MenuItem),
+    /// Neither the setup nor the menu is open, so the key is for the board.
+    Board,
+}
+
+/// What a local game keeps besides its board: the setup before it, the menu over it, the clock,
+/// and whether it was saved and recorded.
+pub struct Session {
+    menu: &'static [MenuItem],
+    menu_state: ListState,
+    pub has_menu_open: bool,
+    menu_area: Rect,
+    pub in_setup: bool,
+    setup_state: ListState,
+    setup_area: Rect,
+    pub status: Option<String>,
+    pub save_path: Option<PathBuf>,
+    pub clock: Option<Clock>,
+    pub on_timeout: OnTimeout,
+    /// The player who lost on time.
+    pub out_of_time: Option<Square>,
+    /// Whether the finished game went into the statistics.
+    pub recorded: bool,
+}
+
+impl Session {
+    /// A session starting in the setup, with `menu` to open over the game.
+    pub fn new(menu: &'static [MenuItem]) -> Self {
+        Session {
+            menu,
+            menu_state: ListState::default().with_selected(Some(0)),
+            has_menu_open: false,
+            menu_area: Rect::default(),
+            in_setup: true,
+            setup_state: ListState::default().with_selected(Some(0)),
+            setup_area: Rect::default(),
+            status: None,
+            save_path: None,
+            clock: None,
+            on_timeout: OnTimeout::default(),
+            out_of_time: None,
+            recorded: false,
+        }
+    }
+
+    /// Moves through the setup or the menu, whichever is open, and tells what `action` chose there.
+    /// Picking an item closes the menu, except for the items that lead to another screen and may
+    /// come back to it.
+    pub fn choose(&mut self, action: KeyAction) -> Choice {
+        if self.in_setup {
+            let setting = self.setup_state.selected().unwrap_or(0);
+            match action {
+                KeyAction::Confirm => {
+                    self.in_setup = false;
+                    return Choice::Start;
+                }
+                KeyAction::Down => self.setup_state.select_next(),
+                KeyAction::Up => self.setup_state.select_previous(),
+                KeyAction::Left => return Choice::Change(setting, -1),
+                KeyAction::Right => return Choice::Change(setting, 1),
+                _ => {}
+            }
+        } else if self.has_menu_open {
+            match action {
+                KeyAction::Back => self.reset_menu(),
+                KeyAction::Down => self.menu_state.select_next(),
+                KeyAction::Up => self.menu_state.select_previous(),
+                KeyAction::Confirm => {
+                    let Some(&item) = self.menu_state.selected().and_then(|index| self.menu.get(index)) else {
+                        return Choice::None;
+                    };
+                    if !matches!(item, MenuItem::Settings | MenuItem::Menu | MenuItem::Quit) {
+                        self.reset_menu();
+                    }
+                    return Choice::Item(item);
+                }
+                _ => {}
+            }
+        } else {
+            return Choice::Board;
+        }
+        Choice::None
+    }
+
+    /// Points at a setting or a menu item, whichever is open. Returns whether a menu item was
+    /// clicked, or `None` when neither is open and the mouse is for the board.
+    pub fn point(&mut self, mouse_event: &MouseEvent) -> Option<bool> {
+        if self.in_setup {
+            // Clicking a setting only selects it, it is changed with the arrow keys.
+            point_at_item(mouse_event, self.setup_area, &mut self.setup_state, self.setup_area.height as usize);
+            Some(false)
+        } else if self.has_menu_open {
+            Some(point_at_item(mouse_event, self.menu_area, &mut self.menu_state, self.menu.len()))
+        } else {
+            None
+        }
+    }
+
+    /// Closes the menu, selecting its first item for the next time, and clears the status.
+    pub fn reset_menu(&mut self) {
+        self.has_menu_open = false;
+        self.menu_state.select(Some(0));
+        self.status = None;
+    }
+
+    /// The winner, counting a player who ran out of time as beaten by whoever moves after them.
+    pub fn winner(&self, board_winner: Square, players: usize) -> Square {
+        match self.out_of_time {
+            Some(mark) => mark.next(players),
+            None => board_winner,
+        }
+    }
+
+    /// Runs the clock for `turn` while the game is on, that is neither in the setup, under the menu
+    /// nor `over`.
+    pub fn follow_clock(&mut self, turn: Square, moves: usize, over: bool) {
+        let paused = self.in_setup || self.has_menu_open || over;
+        if let Some(clock) = &mut self.clock {
+            clock.follow(turn, moves, paused);
+        }
+    }
+
+    /// Stops the clock while another screen is open over the game. It starts again with the next
+    /// update once the game is back on top.
+    pub fn pause(&mut self, turn: Square, moves: usize) {
+        if let Some(clock) = &mut self.clock {
+            clock.follow(turn, moves, true);
+        }
+    }
+
+    /// Moves the time control a step through the choices, `Off` included.
+    pub fn step_clock(&mut self, delta: isize) {
+        self.clock = TimeControl::step(self.clock.as_ref().map(Clock::control), delta).map(Clock::new);
+    }
+
+    /// Ends the game for `mark` whose time ran out if that loses it. Returns whether it did, as
+    /// otherwise a random move is played for them.
+    pub fn run_out(&mut self, mark: Square) -> bool {
+        if self.on_timeout != OnTimeout::Lose {
+            return false;
+        }
+        self.out_of_time = Some(mark);
+        self.has_menu_open = true;
+        true
+    }
+
+    /// Forgets what belonged to the last game: its clock, a loss on time and that it was recorded.
+    pub fn start_over(&mut self) {
+        self.out_of_time = None;
+        self.recorded = false;
+        if let Some(clock) = &mut self.clock {
+            clock.reset();
+        }
+    }
+
+    /// Whether the game is over with `winner` but not in the statistics yet.
+    pub fn needs_recording(&self, winner: Square) -> bool {
+        !self.recorded && winner != Square::None
+    }
+
+    /// Adds the finished game to the statistics.
+    pub fn record(&mut self, result: &GameResult) {
+        self.recorded = true;
+        if let Err(error) = stats::record(result) {
+            self.status = Some(format!("Recording the result failed: {error}"));
+        }
+    }
+
+    /// Saves `board` with the clock, to the file the game was saved to or loaded from if any.
+    pub fn save(&mut self, scores: Vec<u32>, first: Square, board: SavedBoard) {
+        let clock = self.clock.as_ref().map(|clock| SavedClock::new(clock, self.on_timeout, self.out_of_time));
+        self.status = Some(match save::save(&SavedGame::new(scores, first, board, clock), self.save_path.as_deref()) {
+            Ok(path) => {
+                self.save_path = Some(path);
+                "Game saved".to_string()
+            }
+            Err(error) => format!("Saving failed: {error}"),
+        });
+    }
+
+    pub fn export(&mut self, record: &Record) {
+        self.status = Some(match save::export(record) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(error) => format!("Export failed: {error}"),
+        });
+    }
+
+    /// Continues a game loaded from `path`, skipping the setup.
+    pub fn restore(&mut self, path: PathBuf, clock: Option<SavedClock>) {
+        if let Some(clock) = clock {
+            self.clock = Some(clock.clock());
+            self.on_timeout = clock.on_timeout;
+            self.out_of_time = clock.out_of_time;
+        }
+        self.in_setup = false;
+        self.save_path = Some(path);
+    }
+
+    /// Each player's points, name and time left, with the player to move highlighted. With two
+    /// players the second is mirrored as `time name points`.
+    pub fn scoreboard(&self, scores: &[u32], turn: Square, name: impl Fn(Square) -> String, thinking: bool) -> Line<'static> {
+        let players = scores.len();
+        let mut spans = Vec::new();
+        for (i, mark) in MARKS[..players].iter().copied().enumerate() {
+            let time = self.clock.as_ref().map(|clock| clock.show(mark));
+            let score = scores[i];
+            let name = name(mark);
+            let mut span = Span::from(match time {
+                Some(time) if players == 2 && i == 1 => format!("{time} {name} {score}"),
+                None if players == 2 && i == 1 => format!("{name} {score}"),
+                Some(time) => format!("{score} {name} {time}"),
+                None => format!("{score} {name}"),
+            });
+            if turn == mark {
+                span = span.style(Style::new().add_modifier(Modifier::REVERSED));
+            }
+            if i > 0 {
+                spans.push(Span::from(" | "));
+            }
+            spans.push(span);
+        }
+        if thinking {
+            spans.push(Span::from(" thinking…"));
+        }
+        Line::from(spans)
+    }
+
+    /// The header of a finished game: a draw, a loss on time or who won.
+    pub fn ending(&self, winner: Square, name: impl Fn(Square) -> String) -> Text<'static> {
+        let text = if winner == Square::Draw {
+            Text::from("Draw!")
+        } else if let Some(mark) = self.out_of_time {
+            Text::from(format!("{} ran out of time, {} wins!", name(mark), name(winner)))
+        } else {
+            Text::from(format!("{} wins!", name(winner)))
+        };
+        text.style(Style::new().add_modifier(Modifier::REVERSED))
+    }
+
+    /// The rows of the setup for the clock.
+    pub fn clock_settings(&self) -> [String; 2] {
+        [
+            format!("Clock {:>10}", self.clock.as_ref().map_or("Off".to_string(), |clock| clock.control().name())),
+            format!("Time out {:>7}", self.on_timeout.name()),
+        ]
+    }
+
+    /// Draws `header` above the board and the status below it. Returns the area of the board.
+    pub fn render_around(&self, frame: &mut Frame, area: Rect, header: Text) -> Rect {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Percentage(75),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Fill(1),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        // The header gets the whole width, the names and clocks don't fit above the board alone.
+        frame.render_widget(Paragraph::new(header).centered(), Rect { x: area.x, width: area.width, ..layout[1] });
+        self.render_status(frame, layout[4]);
+        layout[3]
+    }
+
+    pub fn render_status(&self, frame: &mut Frame, area: Rect) {
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).centered(), area);
+        }
+    }
+
+    /// Draws the menu or the setup listing `settings` over the board in `area`, whichever is open.
+    pub fn render_over(&mut self, frame: &mut Frame, area: Rect, settings: Vec<String>) {
+        let highlight = Style::new().add_modifier(Modifier::REVERSED);
+        if self.has_menu_open {
+            self.menu_area = centered(area, 8, self.menu.len() as u16);
+            frame.render_widget(Clear, self.menu_area);
+            frame.render_stateful_widget(List::new(self.menu.iter().map(|item| item.name())).highlight_style(highlight), self.menu_area, &mut self.menu_state);
+        } else if self.in_setup {
+            let rows = settings.len() as u16;
+            let setup_area = centered(area, 16, rows + 3);
+            let setup_layout = Layout::default().direction(Direction::Vertical)
+                .constraints(vec![
+                    Constraint::Length(rows),
+                    Constraint::Length(1),
+                    Constraint::Length(2)]).split(setup_area);
+            frame.render_widget(Clear, setup_area);
+            frame.render_stateful_widget(List::new(settings).highlight_style(highlight), setup_layout[0], &mut self.setup_state);
+            self.setup_area = setup_layout[0];
+            frame.render_widget(Paragraph::new("Change with ←/→\nand hit Enter").centered(), setup_layout[2]);
+        }
+    }
+}
+
+/// Quits, asking first if that would lose a game `in_progress`.
+pub fn quit(in_progress: bool) -> Action {
+    if in_progress {
+        return Action::Push(Box::new(Confirm::new("Quit the unfinished game?", Action::Quit)));
+    }
+    Action::Quit
+}
+
+/// A `width`×`height` area in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    Layout::default().direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Fill(1),
+            Constraint::Length(width),
+            Constraint::Fill(1)]
+        ).split(Layout::default().direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Fill(1),
+            Constraint::Length(height),
+            Constraint::Fill(1)]).split(area)[1])[1]
+}
@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListState, Paragraph};
+use crate::action::Action;
+use crate::components::{point_at_item, Component};
+use crate::components::help::Help;
+use crate::config;
+use crate::config::{Config, First, Mode};
+use crate::keymap;
+use crate::keymap::KeyAction;
+
+const ROWS: [&str; 19] = [
+    "X name", "O name", "△ name", "□ name", "X color", "O color", "△ color", "□ color", "Selector", "Winning line", "Grid", "Super grid",
+    "Mode", "Width", "Height", "Win length", "Super size", "First", "Numpad keys",
+];
+/// How many rows at the top are player names.
+const NAMES: usize = 4;
+const COLORS: [Color; 16] = [
+    Color::Black, Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+    Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow, Color::LightBlue, Color::LightMagenta, Color::LightCyan, Color::White,
+];
+const MAX_NAME_LENGTH: usize = 12;
+
+pub struct Settings {
+    config: Config,
+    list_state: ListState,
+    list_area: Rect,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for Settings {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None)
+        }
+        // Letters go into the name, bound ones like j, k and q included, so only the arrow keys
+        // move off a name row. The hint below the list says so.
+        if self.editing_name() {
+            if let Some(char) = keymap::typed_char(&key_event) {
+                let name = self.name();
+                if name.chars().count() < MAX_NAME_LENGTH {
+                    name.push(char);
+                }
+                return Ok(Action::None);
+            }
+            if key_event.code == KeyCode::Backspace {
+                self.name().pop();
+                return Ok(Action::None);
+            }
+        }
+        match config::key_action(&key_event) {
+            Some(KeyAction::Back) => return Ok(Action::Pop),
+            Some(KeyAction::Quit) => return Ok(Action::Quit),
+            Some(KeyAction::Help) => return Ok(Action::Push(Box::new(Help::new()))),
+            Some(KeyAction::Confirm) => {
+                config::set(self.config.clone());
+                self.status = Some(match config::save(&self.config) {
+                    Ok(path) => format!("Saved to {}", path.display()),
+                    Err(error) => format!("Saving failed: {error}"),
+                });
+            }
+            Some(KeyAction::Down) => self.list_state.select_next(),
+            Some(KeyAction::Up) => self.list_state.select_previous(),
+            Some(KeyAction::Left) => self.change_setting(-1),
+            Some(KeyAction::Right) => self.change_setting(1),
+            _ => ()
+        }
+        Ok(Action::None)
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        point_at_item(&mouse_event, self.list_area, &mut self.list_state, ROWS.len());
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let config = &self.config;
+        let colors = [config.colors.x, config.colors.o, config.colors.triangle, config.colors.block, config.colors.selector, config.colors.winning_line, config.colors.grid, config.colors.super_grid];
+        let names = [&config.players.x, &config.players.o, &config.players.triangle, &config.players.block];
+        let mut rows: Vec<Line> = ROWS.iter().zip(names).map(|(label, name)| Line::from(format!("{label:<13}{name:>13}"))).collect();
+        for (label, color) in ROWS[NAMES..12].iter().zip(colors) {
+            rows.push(Line::from(vec![Span::from(format!("{label:<13}")), Span::from(format!("{:>13}", color.to_string())).style(Style::new().fg(color))]));
+        }
+        let defaults = &config.defaults;
+        let mode = if defaults.mode == Mode::Normal { "Normal" } else { "Super" };
+        let first = if defaults.first == First::X { "X" } else { "O" };
+        let numpad = if config.keys.numpad { "On" } else { "Off" };
+        for (label, value) in ROWS[12..].iter().zip([mode.to_string(), defaults.width.to_string(), defaults.height.to_string(), defaults.win_length.to_string(), defaults.super_size.to_string(), first.to_string(), numpad.to_string()]) {
+            rows.push(Line::from(format!("{label:<13}{value:>13}")));
+        }
+
+        let hint = if self.editing_name() { "Type a name, move with ↑/↓, Enter to save" } else { "Change with ←/→, Enter to save, Esc to go back" };
+        let status = self.status.clone().unwrap_or_else(|| hint.to_string());
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(rows.len() as u16),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(26),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new("Settings").centered(), layout[1]);
+        frame.render_stateful_widget(List::new(rows).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), layout[2], &mut self.list_state);
+        self.list_area = layout[2];
+        frame.render_widget(Paragraph::new(status).centered(), Rect { x: area.x, width: area.width, ..layout[4] });
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Settings { config: config::get(), list_state: ListState::default().with_selected(Some(0)), list_area: Rect::default(), status: None }
+    }
+
+    fn editing_name(&self) -> bool {
+        self.list_state.selected().is_some_and(|selected| selected < NAMES)
+    }
+
+    fn name(&mut self) -> &mut String {
+        let players = &mut self.config.players;
+        [&mut players.x, &mut players.o, &mut players.triangle, &mut players.block].into_iter().nth(self.list_state.selected().unwrap_or(0)).unwrap()
+    }
+
+    fn change_setting(&mut self, delta: isize) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        let colors = &mut self.config.colors;
+        let defaults = &mut self.config.defaults;
+        let change = |value: usize, min: usize, max: usize| value.saturating_add_signed(delta).clamp(min, max);
+        match selected {
+            4..=11 => {
+                let color = [&mut colors.x, &mut colors.o, &mut colors.triangle, &mut colors.block, &mut colors.selector, &mut colors.winning_line, &mut colors.grid, &mut colors.super_grid]
+                    .into_iter().nth(selected - NAMES).unwrap();
+                let index = COLORS.iter().position(|other| other == color).map_or(0, |index| (index as isize + delta).rem_euclid(COLORS.len() as isize) as usize);
+                *color = COLORS[index];
+            }
+            12 => defaults.mode = if defaults.mode == Mode::Normal { Mode::Super } else { Mode::Normal },
+            13 => defaults.width = change(defaults.width, 3, usize::MAX),
+            14 => defaults.height = change(defaults.height, 3, usize::MAX),
+            15 => defaults.win_length = change(defaults.win_length, 3, defaults.width.max(defaults.height)),
+            16 => defaults.super_size = change(defaults.super_size, 3, usize::MAX),
+            17 => defaults.first = if defaults.first == First::X { First::O } else { First::X },
+            18 => self.config.keys.numpad = !self.config.keys.numpad,
+            _ => {}
+        }
+        defaults.win_length = defaults.win_length.min(defaults.width.max(defaults.height));
+        self.status = None;
+    }
+}
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Row, Table, TableState};
+use crate::action::Action;
+use crate::components::Component;
+use crate::components::help::Help;
+use crate::config;
+use crate::keymap::KeyAction;
+use crate::stats;
+use crate::stats::Summary;
+
+pub struct Statistics {
+    summary: Summary,
+    table_state: TableState,
+    status: Option<String>,
+}
+
+#[async_trait]
+impl Component for Statistics {
+    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(Action::None);
+        }
+        match config::key_action(&key_event) {
+            Some(KeyAction::Quit) => return Ok(Action::Quit),
+            Some(KeyAction::Help) => return Ok(Action::Push(Box::new(Help::new()))),
+            Some(KeyAction::Back) => return Ok(Action::Pop),
+            Some(KeyAction::Down) => self.select(1),
+            Some(KeyAction::Up) => self.select(-1),
+            _ => {}
+        }
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let players = &self.summary.players;
+        let selected = self.table_state.selected().and_then(|selected| players.get(selected));
+        let variants = selected.map_or(0, |player| player.by_variant.len());
+        let status = match &self.status {
+            Some(status) => status.as_str(),
+            None if players.is_empty() => "No finished games yet",
+            None => "↑/↓ choose a player, Esc to go back",
+        };
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(2),
+                Constraint::Length(players.len() as u16 + 1),
+                Constraint::Length(1),
+                Constraint::Length((variants as u16 + 1).max(8)),
+                Constraint::Length(1),
+                Constraint::Length(self.summary.first_player.len() as u16 + 1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1)]
+            )
+            .split(Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Fill(1),
+                    Constraint::Length(76),
+                    Constraint::Fill(1)]
+                ).split(area)[1]
+            );
+        frame.render_widget(Paragraph::new("Statistics").centered(), layout[1]);
+        frame.render_widget(Paragraph::new(status).centered(), layout[8]);
+        if players.is_empty() {
+            return;
+        }
+
+        let rows = players.iter().map(|player| Row::new(vec![
+            player.name.clone(),
+            player.total.games().to_string(),
+            player.total.wins.to_string(),
+            player.total.losses.to_string(),
+            player.total.draws.to_string(),
+            format!("{}%", player.total.win_rate()),
+            player.streak.to_string(),
+            player.best_streak.to_string(),
+            format!("{:.1}", player.average_length()),
+        ]));
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(5), Constraint::Length(4), Constraint::Length(4), Constraint::Length(4), Constraint::Length(5), Constraint::Length(6), Constraint::Length(4), Constraint::Length(9)])
+            .header(Row::new(["Player", "Games", "W", "L", "D", "Won", "Streak", "Best", "Avg moves"]).style(Style::new().add_modifier(Modifier::BOLD)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, layout[2], &mut self.table_state);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(2), Constraint::Length(30)])
+            .split(layout[4]);
+        if let Some(player) = selected {
+            let rows = player.by_variant.iter().map(|(variant, tally)| Row::new(vec![
+                variant.name(),
+                tally.wins.to_string(),
+                tally.losses.to_string(),
+                tally.draws.to_string(),
+            ]));
+            let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(4), Constraint::Length(4), Constraint::Length(4)])
+                .header(Row::new([format!("{} by board", player.name), "W".to_string(), "L".to_string(), "D".to_string()]).style(Style::new().add_modifier(Modifier::BOLD)));
+            frame.render_widget(table, columns[0]);
+        }
+        let bars: Vec<Bar> = players.iter()
+            .map(|player| Bar::default().value(player.total.wins.into()).text_value(player.total.wins.to_string()).label(player.name.clone().into()))
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::bordered().title("Wins"))
+            .bar_width(5)
+            .bar_gap(1)
+            .bar_style(Style::new().fg(config::colors().selector))
+            .data(BarGroup::default().bars(&bars));
+        frame.render_widget(chart, columns[2]);
+
+        let rows = self.summary.first_player.iter().map(|(variant, tally)| Row::new(vec![
+            variant.name(),
+            tally.wins.to_string(),
+            tally.losses.to_string(),
+            tally.draws.to_string(),
+            format!("{}%", tally.win_rate()),
+        ]));
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(12), Constraint::Length(12), Constraint::Length(6), Constraint::Length(6)])
+            .header(Row::new(["Moving first", "First won", "Second won", "Draws", "Won"]).style(Style::new().add_modifier(Modifier::BOLD)));
+        frame.render_widget(table, layout[6]);
+    }
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        let mut statistics = Statistics { summary: Summary::new(&[]), table_state: TableState::default().with_selected(Some(0)), status: None };
+        match stats::history() {
+            Ok(history) => statistics.summary = Summary::new(&history),
+            Err(error) => statistics.status = Some(format!("Cannot read the history: {error}")),
+        }
+        statistics
+    }
+
+    fn select(&mut self, delta: isize) {
+        let last = self.summary.players.len().saturating_sub(1);
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state.select(Some(selected.saturating_add_signed(delta).min(last)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+    use crate::engine::{Rules, Square};
+    use crate::stats::{GameResult, Variant};
+
+    #[tokio::test]
+    async fn shows_the_selected_players_boards() {
+        let normal = Variant::Normal { width: 3, height: 3, win_length: 3, rules: Rules::default(), players: 2 };
+        let history = [
+            GameResult::new(normal, "Ann", "Bob", Square::X, Square::X, 5),
+            GameResult::new(Variant::Super { size: 3 }, "Bob", "Ann", Square::X, Square::X, 40),
+        ];
+        let statistics = Statistics { summary: Summary::new(&history), table_state: TableState::default().with_selected(Some(0)), status: None };
+        let mut harness = Harness::with_size(statistics, 80, 30);
+        assert!(harness.contains("Ann by board"));
+        harness.keys(&["j"]).await;
+        assert!(harness.contains("Bob by board"));
+        assert!(harness.contains("Normal 3×3, 3 in a row        0    1    0"));
+        assert!(harness.contains("Super 3×3                     1    0    0"));
+    }
+}
@@ -1,13 +1,17 @@
 use async_trait::async_trait;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Color;
 use crate::action::Action;
 use crate::components::Component;
-use crate::components::game::{Game, Square};
+use crate::components::game::Game;
+use crate::config;
+use crate::engine::Square;
+use crate::engine::{MoveError, Outcome, SuperBoard, SuperMove};
+use crate::keymap::KeyAction;
 
 pub struct SuperGame {
+    pub board: SuperBoard,
     pub managing_game: Game,
     pub games: Vec<Vec<Game>>,
     pub selecting_game: bool,
@@ -16,39 +20,15 @@ pub struct SuperGame {
 #[async_trait]
 impl Component for SuperGame {
     async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<Action> {
-        if self.selecting_game {
-            if key_event.code == KeyCode::Enter {
-                let game = &mut self.games[self.managing_game.selected.0 as usize][self.managing_game.selected.1 as usize];
-                if game.winner.0 == Square::None {
-                    self.selecting_game = false;
-                    self.managing_game.show_selector = false;
-                    game.show_selector = true;
-                    game.turn = self.managing_game.turn;
-                }
-            } else {
-                self.managing_game.handle_key_event(key_event).await?;
-            }
-        } else {
-            let game = &mut self.games[self.managing_game.selected.0 as usize][self.managing_game.selected.1 as usize];
-            let selected = game.selected;
-            game.handle_key_event(key_event).await?;
-            if game.turn != self.managing_game.turn {
-                if game.winner.0 != Square::None {
-                    self.managing_game.hit();
-                }
-                self.managing_game.selected = selected;
-                self.managing_game.turn = game.turn;
-                game.show_selector = false;
-                let new_game = &mut self.games[selected.0 as usize][selected.1 as usize];
-                if new_game.winner.0 != Square::None {
-                    self.selecting_game = true;
-                    self.managing_game.selected = (1.0, 1.0);
-                    self.managing_game.show_selector = true;
-                } else {
-                    new_game.show_selector = true;
-                    new_game.turn = self.managing_game.turn;
-                }
-            }
+        if let Some(action) = config::key_action(&key_event) {
+            self.act(action);
+        }
+        Ok(Action::None)
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        if self.point(&mouse_event) {
+            self.act(KeyAction::Confirm);
         }
         Ok(Action::None)
     }
@@ -60,18 +40,18 @@ impl Component for SuperGame {
             .constraints(vec![Constraint::Fill(1); self.games.len()])
             .spacing(2)
             .split(area);
-        for column in 0..columns.len() {
+        for column in columns.iter() {
             let mut rows =Layout::default()
                 .direction(Direction::Vertical)
                 .constraints(vec![Constraint::Fill(1); self.games.len()])
                 .spacing(2)
-                .split(columns[column]).to_vec();
+                .split(*column).to_vec();
             rows.reverse();
             game_areas.push(rows);
         }
-        for x in 0..self.games.len() {
-            for y in 0..self.games.len() {
-                self.games[x][y].render(frame, game_areas[x][y]);
+        for (games, areas) in self.games.iter_mut().zip(game_areas) {
+            for (game, game_area) in games.iter_mut().zip(areas) {
+                game.render(frame, game_area);
             }
         }
         self.managing_game.render(frame, area);
@@ -81,46 +61,265 @@ impl Component for SuperGame {
 impl SuperGame {
     pub fn new() -> Self {
         let mut managing_game = Game::new();
-        managing_game.line_color = Color::Yellow;
-        let mut games = vec![vec![Game::new(); 3]; 3];
-        for x in 0..games.len() {
-            for y in 0..games.len() {
-                games[x][y].show_selector = false;
+        managing_game.line_color = config::colors().super_grid;
+        SuperGame { board: SuperBoard::new(3), managing_game, games: Self::sub_games(3), selecting_game: true }
+    }
+
+    /// Moves the selector, chooses the selected board or plays the selected square. On a 3×3
+    /// super board the numeric keypad picks the board or the square within it.
+    pub fn act(&mut self, action: KeyAction) {
+        let numpad = self.board.size() == 3;
+        if self.selecting_game {
+            match action {
+                KeyAction::Cell(cell) if numpad => {
+                    self.managing_game.selected = cell;
+                    self.choose_board();
+                }
+                KeyAction::Confirm => self.choose_board(),
+                KeyAction::Cell(_) => (),
+                action => self.managing_game.act(action),
+            }
+        } else {
+            let board = self.managing_game.selected;
+            match action {
+                KeyAction::Cell(cell) if numpad => {
+                    self.games[board.0][board.1].selected = cell;
+                    // Occupied squares are simply ignored, like in the normal game.
+                    let _ = self.play((board, cell));
+                }
+                KeyAction::Confirm => {
+                    let _ = self.play((board, self.games[board.0][board.1].selected));
+                }
+                KeyAction::Cell(_) => (),
+                action => self.games[board.0][board.1].act(action),
+            }
+        }
+    }
+
+    fn choose_board(&mut self) {
+        let game = &mut self.games[self.managing_game.selected.0][self.managing_game.selected.1];
+        if game.board.outcome() == Outcome::InProgress {
+            self.selecting_game = false;
+            self.managing_game.show_selector = false;
+            game.show_selector = true;
+        }
+    }
+
+    /// The move under the selector, `None` while a board is still being chosen.
+    pub fn selected_move(&self) -> Option<SuperMove> {
+        if self.selecting_game {
+            return None;
+        }
+        let board = self.managing_game.selected;
+        Some((board, self.games[board.0][board.1].selected))
+    }
+
+    /// Moves the selector to the board or square under the mouse. Returns whether it was clicked.
+    pub fn point(&mut self, mouse_event: &MouseEvent) -> bool {
+        if !matches!(mouse_event.kind, MouseEventKind::Moved | MouseEventKind::Down(MouseButton::Left)) {
+            return false;
+        }
+        if !self.selecting_game {
+            let board = self.managing_game.selected;
+            let game = &mut self.games[board.0][board.1];
+            if self.board.active().is_some() || game.is_over(mouse_event) || !self.managing_game.is_over(mouse_event) {
+                return game.point(mouse_event);
+            }
+            // With a free choice, pointing away from the chosen board goes back to choosing one.
+            game.show_selector = false;
+            self.selecting_game = true;
+            self.managing_game.show_selector = true;
+        }
+        self.managing_game.point(mouse_event)
+    }
+
+    /// Plays `mv` for the player to move and moves the selector to the board the opponent is sent to.
+    pub fn play(&mut self, mv: SuperMove) -> Result<Outcome, MoveError> {
+        let outcome = self.board.play(mv)?;
+        self.finish_move(outcome);
+        Ok(outcome)
+    }
+
+    /// Takes back the last move, restoring won boards, the point scored and the board the player is sent to.
+    pub fn undo(&mut self) -> bool {
+        let outcome = self.board.outcome();
+        let Some((board, square)) = self.board.undo() else {
+            return false;
+        };
+        if let Outcome::Win(winner, _) = outcome {
+            self.managing_game.take_point(winner);
+        }
+        self.sync();
+        self.focus();
+        if self.selecting_game {
+            self.managing_game.selected = board;
+        } else {
+            self.games[board.0][board.1].selected = square;
+        }
+        true
+    }
+
+    /// Plays the last undone move again.
+    pub fn redo(&mut self) -> bool {
+        let Some(outcome) = self.board.redo() else {
+            return false;
+        };
+        self.finish_move(outcome);
+        true
+    }
+
+    fn finish_move(&mut self, outcome: Outcome) {
+        if let Outcome::Win(square, _) = outcome {
+            self.managing_game.scores[square.index()] += 1;
+        }
+        self.sync();
+        self.focus();
+    }
+
+    /// Shows the selector on the board the player is sent to, or on the overall board if they may choose.
+    fn focus(&mut self) {
+        let in_progress = self.board.outcome() == Outcome::InProgress;
+        for game in self.games.iter_mut().flatten() {
+            game.show_selector = false;
+        }
+        match self.board.active() {
+            Some(active) => {
+                self.selecting_game = false;
+                self.managing_game.selected = active;
+                self.managing_game.show_selector = false;
+                self.games[active.0][active.1].show_selector = in_progress;
+            }
+            None => {
+                self.selecting_game = true;
+                self.managing_game.selected = (self.board.size() / 2, self.board.size() / 2);
+                self.managing_game.show_selector = in_progress;
+            }
+        }
+    }
+
+    /// Copies the engine state into the boards drawn by the views.
+    fn sync(&mut self) {
+        self.managing_game.board = self.board.overall().clone();
+        for (x, games) in self.games.iter_mut().enumerate() {
+            for (y, game) in games.iter_mut().enumerate() {
+                game.board = self.board.board((x, y)).clone();
             }
         }
-        SuperGame { managing_game, games, selecting_game: true }
     }
 
     pub fn restart(&mut self) {
         self.rematch();
-        self.managing_game.scores = (0, 0);
+        self.managing_game.scores = vec![0; 2];
     }
 
     pub fn rematch(&mut self) {
-        let mut games = vec![vec![Game::new(); self.games.len()]; self.games.len()];
-        for x in 0..games.len() {
-            for y in 0..games.len() {
-                games[x][y].show_selector = false;
-                games[x][y].set_size(self.games.len());
-            }
-        }
-        self.games = games;
+        self.replace_board(self.board.size());
+        self.games = Self::sub_games(self.board.size());
         self.managing_game.rematch();
         self.selecting_game = true;
     }
 
     pub fn set_size(&mut self, num: usize) {
         if num > 2 {
-            self.managing_game.selected = ((num / 2) as f64, (num / 2) as f64);
-            self.managing_game.board = vec![vec![Square::None; num]; num];
-            let mut games = vec![vec![Game::new(); num]; num];
-            for x in 0..games.len() {
-                for y in 0..games.len() {
-                    games[x][y].show_selector = false;
-                    games[x][y].set_size(num);
-                }
-            }
-            self.games = games;
+            self.replace_board(num);
+            self.managing_game.set_size(num, num);
+            self.managing_game.set_win_length(num);
+            self.games = Self::sub_games(num);
+        }
+    }
+
+    /// Lets `first` start this and following games. Only takes effect before the first move.
+    pub fn set_first(&mut self, first: Square) {
+        if self.board.moves().is_empty() {
+            self.board.set_first(first);
         }
     }
-}
\ No newline at end of file
+
+    /// Starts over on a new board, keeping who moves first.
+    fn replace_board(&mut self, size: usize) {
+        let first = self.board.first();
+        self.board = SuperBoard::new(size);
+        self.board.set_first(first);
+    }
+
+    fn sub_games(num: usize) -> Vec<Vec<Game>> {
+        let mut game = Game::new();
+        game.show_selector = false;
+        game.set_size(num, num);
+        game.set_win_length(num);
+        vec![vec![game; num]; num]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::harness::Harness;
+
+    #[tokio::test]
+    async fn square_played_forces_the_matching_board() {
+        let mut harness = Harness::new(SuperGame::new());
+        // The selector starts on the center board and the center square.
+        harness.keys(&["Enter", "l", "Enter"]).await;
+        let game = &harness.component;
+        assert_eq!(game.board.moves(), [((1, 1), (2, 1))]);
+        assert_eq!(game.board.active(), Some((2, 1)));
+        assert!(!game.selecting_game);
+        assert_eq!(game.managing_game.selected, (2, 1));
+        assert!(game.games[2][1].show_selector);
+        assert!(!game.games[1][1].show_selector);
+    }
+
+    #[tokio::test]
+    async fn moving_never_leaves_the_forced_board() {
+        let mut harness = Harness::new(SuperGame::new());
+        harness.keys(&["Enter", "Enter", "h", "h", "h", "Enter"]).await;
+        assert_eq!(harness.component.board.moves(), [((1, 1), (1, 1)), ((1, 1), (0, 1))]);
+    }
+
+    #[test]
+    fn playing_outside_the_forced_board_fails() {
+        let mut game = SuperGame::new();
+        game.play(((0, 0), (2, 2))).unwrap();
+        assert_eq!(game.play(((0, 0), (0, 0))), Err(MoveError::WrongBoard));
+        assert!(game.play(((2, 2), (0, 0))).is_ok());
+    }
+
+    #[test]
+    fn sent_to_a_won_board_chooses_freely() {
+        let mut game = SuperGame::new();
+        let moves = [
+            ((0, 0), (0, 0)), ((0, 0), (1, 1)), ((1, 1), (0, 0)), ((0, 0), (2, 2)), ((2, 2), (0, 0)),
+            ((0, 0), (1, 2)), ((1, 2), (0, 0)), ((0, 0), (0, 1)), ((0, 1), (0, 0)), ((0, 0), (1, 0)),
+        ];
+        for mv in moves {
+            game.play(mv).unwrap();
+        }
+        assert_eq!(game.board.overall().get((0, 0)), Square::Circle);
+        assert_eq!(game.board.active(), Some((1, 0)));
+
+        game.play(((1, 0), (0, 0))).unwrap();
+        assert_eq!(game.board.active(), None);
+        assert!(game.selecting_game);
+        assert!(game.managing_game.show_selector);
+
+        // The won board itself cannot be chosen.
+        game.managing_game.selected = (0, 0);
+        game.act(KeyAction::Confirm);
+        assert!(game.selecting_game);
+        game.managing_game.selected = (2, 0);
+        game.act(KeyAction::Confirm);
+        assert!(!game.selecting_game);
+    }
+
+    #[test]
+    fn numpad_picks_the_board_then_the_square() {
+        let mut game = SuperGame::new();
+        game.act(KeyAction::Cell((0, 2)));
+        assert!(!game.selecting_game);
+        assert_eq!(game.managing_game.selected, (0, 2));
+        game.act(KeyAction::Cell((2, 0)));
+        assert_eq!(game.board.moves(), [((0, 2), (2, 0))]);
+        assert_eq!(game.board.active(), Some((2, 0)));
+    }
+}
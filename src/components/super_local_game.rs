@@ -1,24 +1,60 @@
 use async_trait::async_trait;
-use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use ratatui::crossterm::event::KeyCode::Char;
+use ratatui::crossterm::event::{KeyEvent, KeyEventKind, MouseEvent};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::Rect;
 use ratatui::prelude::Text;
-use ratatui::style::{Modifier, Style};
-use ratatui::text::Span;
-use ratatui::widgets::{Clear, List, ListState, Paragraph};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use rand::seq::SliceRandom;
+use color_eyre::eyre::bail;
+use tokio::task::JoinHandle;
 use crate::action::Action;
+use crate::ai::mcts;
+use crate::ai::mcts::Budget;
 use crate::components::Component;
-use crate::components::game::Square;
-use crate::components::game::Square::Draw;
-use crate::components::main_menu::MainMenu;
+use crate::components::help::Help;
+use crate::components::session;
+use crate::components::session::{Choice, MenuItem, Session, MENU};
+use crate::components::settings::Settings;
+use crate::engine::{Square, SuperMove};
+use crate::config;
+use crate::event;
+use crate::keymap::KeyAction;
+use crate::notation::Record;
+use crate::save;
+use crate::save::{SavedBoard, SavedGame};
+use crate::stats::{GameResult, Variant};
 use crate::components::super_game::SuperGame;
 
 pub struct SuperLocalGame {
     game: SuperGame,
-    menu_state: ListState,
-    has_menu_open: bool,
-    in_setup: bool,
+    session: Session,
+    computer: Option<Budget>,
+    thinking: Option<JoinHandle<Option<SuperMove>>>,
+    /// Set to end the running search early once its move is no longer wanted.
+    stop: Arc<AtomicBool>,
+}
+
+const THINK_TIMES: [Duration; 5] = [
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Iteration budgets to choose from, `None` searching for the whole think time.
+const ITERATIONS: [Option<usize>; 5] = [None, Some(1_000), Some(10_000), Some(100_000), Some(1_000_000)];
+
+fn iterations_name(iterations: Option<usize>) -> String {
+    match iterations {
+        None => "Any".to_string(),
+        Some(count) if count >= 1_000_000 && count.is_multiple_of(1_000_000) => format!("{}M", count / 1_000_000),
+        Some(count) if count >= 1_000 && count.is_multiple_of(1_000) => format!("{}k", count / 1_000),
+        Some(count) => count.to_string(),
+    }
 }
 
 #[async_trait]
@@ -28,138 +64,396 @@ impl Component for SuperLocalGame {
             return Ok(Action::None);
         }
 
-        if self.in_setup {
-            match key_event.code {
-                KeyCode::Enter => {
-                    self.in_setup = false;
-                    self.game.managing_game.show_selector = true;
-                }
-                Char('j') | KeyCode::Down => self.game.set_size(self.game.managing_game.board.len() - 1),
-                Char('k') | KeyCode::Up => self.game.set_size(self.game.managing_game.board.len() + 1),
-                _ => {}
+        match config::key_action(&key_event) {
+            Some(action) => self.act(action),
+            None => Ok(Action::None),
+        }
+    }
+
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> color_eyre::Result<Action> {
+        let clicked = match self.session.point(&mouse_event) {
+            Some(clicked) => clicked,
+            None => self.thinking.is_none() && self.game.point(&mouse_event),
+        };
+        if clicked {
+            return self.act(KeyAction::Confirm);
+        }
+        Ok(Action::None)
+    }
+
+    async fn update(&mut self) -> color_eyre::Result<Action> {
+        self.record_result();
+        self.follow_clock();
+        if let Some(mark) = self.session.clock.as_ref().and_then(|clock| clock.expired()) {
+            self.run_out(mark)?;
+            self.follow_clock();
+            return Ok(Action::Render);
+        }
+        if self.computer_moves().await? {
+            self.follow_clock();
+            return Ok(Action::Render);
+        }
+        if self.session.clock.as_mut().is_some_and(|clock| clock.changed()) {
+            return Ok(Action::Render);
+        }
+        Ok(Action::None)
+    }
+
+    fn pause(&mut self) {
+        self.session.pause(self.game.board.turn(), self.game.board.moves().len());
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let winner = self.winner();
+        let name = |mark| self.player_name(mark);
+        let header = if winner == Square::None {
+            Text::from(self.session.scoreboard(&self.game.managing_game.scores, self.game.board.turn(), name, self.thinking.is_some()))
+        } else {
+            self.session.ending(winner, name)
+        };
+        let board_area = self.session.render_around(frame, area, header);
+        self.game.render(frame, board_area);
+        let settings = self.settings();
+        self.session.render_over(frame, board_area, settings);
+    }
+}
+
+impl SuperLocalGame {
+    pub fn new() -> Self {
+        let config = config::get();
+        let mut game = SuperGame::new();
+        game.set_size(config.defaults.super_size);
+        game.set_first(config.defaults.first.mark());
+        game.managing_game.show_selector = false;
+        SuperLocalGame { game, session: Session::new(&MENU), computer: None, thinking: None, stop: Arc::default() }
+    }
+
+    pub fn vs_computer() -> Self {
+        let mut super_local_game = Self::new();
+        super_local_game.computer = Some(Budget::time(Duration::from_secs(1)));
+        super_local_game
+    }
+
+    /// A game that skips the setup screen, as started from the command line.
+    pub fn with_settings(size: usize, computer: Option<Budget>) -> Self {
+        let mut super_local_game = Self::new();
+        super_local_game.game.set_size(size);
+        super_local_game.game.managing_game.show_selector = true;
+        super_local_game.computer = computer;
+        super_local_game.session.in_setup = false;
+        super_local_game
+    }
+
+    /// Handles `action`, then updates the clock right away so a move stops the mover's time.
+    fn act(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        let action = self.handle_action(action);
+        self.follow_clock();
+        action
+    }
+
+    fn handle_action(&mut self, action: KeyAction) -> color_eyre::Result<Action> {
+        match action {
+            KeyAction::Quit => return Ok(self.quit()),
+            KeyAction::Help => return Ok(Action::Push(Box::new(Help::new()))),
+            _ => {}
+        }
+        match self.session.choose(action) {
+            Choice::None | Choice::Item(MenuItem::Resume) => {}
+            Choice::Start => self.game.managing_game.show_selector = true,
+            Choice::Change(setting, delta) => self.change_setting(setting, delta),
+            Choice::Item(MenuItem::Undo) => self.undo(),
+            Choice::Item(MenuItem::Redo) => self.redo(),
+            Choice::Item(MenuItem::Save) => self.save(),
+            Choice::Item(MenuItem::Export) => self.export(),
+            Choice::Item(MenuItem::Rematch) => {
+                self.game.rematch();
+                self.stop_thinking();
+                self.session.start_over();
             }
-        } else if self.has_menu_open {
-            match key_event.code {
-                KeyCode::Esc => {
-                    self.has_menu_open = false;
-                    self.menu_state = ListState::default().with_selected(Some(0));
-                }
-                Char('j') | KeyCode::Down => self.menu_state.select_next(),
-                Char('k') | KeyCode::Up => self.menu_state.select_previous(),
-                KeyCode::Enter => {
-                    match self.menu_state.selected().unwrap() {
-                        0 => {
-                            self.has_menu_open = false;
-                            self.menu_state = ListState::default().with_selected(Some(0));
-                        },
-                        1 => {
-                            self.game.rematch();
-                            self.menu_state = ListState::default().with_selected(Some(0));
-                            self.has_menu_open = false;
-                        }
-                        2 => {
-                            self.game.restart();
-                            self.menu_state = ListState::default().with_selected(Some(0));
-                            self.has_menu_open = false;
-                        }
-                        3 => return Ok(Action::ChangeComponent(Box::new(MainMenu::new()))),
-                        4 => return Ok(Action::Quit),
-
-                        _ => {}
+            Choice::Item(MenuItem::Restart) => {
+                self.game.restart();
+                self.stop_thinking();
+                self.session.start_over();
+            }
+            Choice::Item(MenuItem::Settings) => return Ok(Action::Push(Box::new(Settings::new()))),
+            Choice::Item(MenuItem::Menu) => return Ok(Action::Pop),
+            Choice::Item(MenuItem::Quit) => return Ok(self.quit()),
+            Choice::Board if action == KeyAction::Undo => self.undo(),
+            Choice::Board if action == KeyAction::Redo => self.redo(),
+            Choice::Board if self.winner() == Square::None => {
+                if action == KeyAction::Back {
+                    self.session.has_menu_open = true;
+                } else if self.thinking.is_none() {
+                    self.game.act(action);
+                    if self.game.managing_game.winner() != Square::None {
+                        self.session.has_menu_open = true;
                     }
                 }
-                _ => {}
             }
+            Choice::Board => self.session.has_menu_open = true,
+        }
 
-            return Ok(Action::None);
-        } else if self.game.managing_game.winner.0 == Square::None {
-            if key_event.code == KeyCode::Esc {
-                self.has_menu_open = true;
-            } else {
-                self.game.handle_key_event(key_event).await?;
-                if self.game.managing_game.winner.0 != Square::None {
-                    self.has_menu_open = true;
-                }
+        Ok(Action::None)
+    }
+
+    fn quit(&self) -> Action {
+        session::quit(!self.game.board.moves().is_empty() && self.winner() == Square::None)
+    }
+
+    fn winner(&self) -> Square {
+        self.session.winner(self.game.managing_game.winner(), 2)
+    }
+
+    fn player_name(&self, mark: Square) -> String {
+        if mark == Square::Circle && self.computer.is_some() { "Computer".to_string() } else { config::players().name(mark).to_string() }
+    }
+
+    /// Restores a game written by `save`, continuing to save to the same file.
+    pub fn load(path: PathBuf, saved: SavedGame) -> color_eyre::Result<Self> {
+        saved.check()?;
+        let SavedBoard::Super { size, moves, think_time, iterations } = saved.board else {
+            bail!("not a super game");
+        };
+        let mut super_local_game = Self::new();
+        super_local_game.game.set_size(size);
+        super_local_game.game.set_first(saved.first);
+        for mv in moves {
+            super_local_game.game.play(mv)?;
+        }
+        super_local_game.game.managing_game.scores = saved.scores;
+        super_local_game.computer = think_time.map(|time| Budget { time, iterations });
+        super_local_game.session.restore(path, saved.clock);
+        let over = super_local_game.winner() != Square::None;
+        super_local_game.session.has_menu_open = over;
+        super_local_game.session.recorded = over;
+        Ok(super_local_game)
+    }
+
+    fn save(&mut self) {
+        let board = SavedBoard::Super {
+            size: self.game.board.size(),
+            moves: self.game.board.moves().to_vec(),
+            think_time: self.computer.map(|budget| budget.time),
+            iterations: self.computer.and_then(|budget| budget.iterations),
+        };
+        self.session.save(self.game.managing_game.scores.clone(), self.game.board.first(), board);
+    }
+
+    fn export(&mut self) {
+        let record = Record::super_game(&self.game.board, &self.player_name(Square::X), &self.player_name(Square::Circle), &save::today());
+        self.session.export(&record);
+    }
+
+    /// Takes back the last move, and against the computer its reply as well so it is the player's turn again.
+    fn undo(&mut self) {
+        if self.session.out_of_time.is_some() {
+            return;
+        }
+        self.stop_thinking();
+        if self.game.undo() {
+            self.session.recorded = false;
+            if self.computer.is_some() && self.game.board.turn() == Square::Circle {
+                self.game.undo();
             }
-        } else {
-            self.has_menu_open = true;
         }
+    }
 
-        Ok(Action::None)
+    fn redo(&mut self) {
+        if self.session.out_of_time.is_some() {
+            return;
+        }
+        self.stop_thinking();
+        if self.game.redo() && self.computer.is_some() && self.game.board.turn() == Square::Circle {
+            self.game.redo();
+        }
     }
 
-    fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(vec![
-                Constraint::Fill(1),
-                Constraint::Length(1),
-                Constraint::Fill(1),
-                Constraint::Percentage(75),
-                Constraint::Fill(1)]
-            )
-            .split(Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Fill(1),
-                    Constraint::Fill(1)]
-                ).split(area)[1]
-            );
-        let text;
-        if self.game.managing_game.winner.0 == Square::None {
-            let mut player1 = Span::from(format!("{} Player1", self.game.managing_game.scores.0));
-            let mut player2 = Span::from(format!("Player2 {}", self.game.managing_game.scores.1));
-            if self.game.managing_game.turn == Square::X {
-                player1 = player1.style(Style::new().add_modifier(Modifier::REVERSED));
-            } else {
-                player2 = player2.style(Style::new().add_modifier(Modifier::REVERSED));
+    /// Has the computer think when it is its turn and plays the move it found. Returns whether it moved.
+    async fn computer_moves(&mut self) -> color_eyre::Result<bool> {
+        let Some(budget) = self.computer else {
+            return Ok(false);
+        };
+        if self.session.in_setup || self.session.has_menu_open || self.winner() != Square::None || self.game.board.turn() != Square::Circle {
+            return Ok(false);
+        }
+        match self.thinking.take() {
+            None => {
+                let board = self.game.board.clone();
+                self.stop = Arc::default();
+                let stop = self.stop.clone();
+                self.thinking = Some(tokio::task::spawn_blocking(move || {
+                    let mv = mcts::search(&board, budget, &stop);
+                    event::wake();
+                    mv
+                }));
             }
-            text = Text::from(ratatui::prelude::Line::from(vec![player1, Span::from(" | "), player2]));
-        } else {
-            if self.game.managing_game.winner.0 == Draw {
-                text = Text::from("Draw!").style(Style::new().add_modifier(Modifier::REVERSED));
-            } else {
-                text = Text::from(if self.game.managing_game.winner.0 == Square::X { "Player1 wins!" } else { "Player2 wins!" }).style(Style::new().add_modifier(Modifier::REVERSED));
+            Some(thinking) if thinking.is_finished() => {
+                if let Some(mv) = thinking.await? {
+                    self.game.play(mv)?;
+                    if self.game.managing_game.winner() != Square::None {
+                        self.session.has_menu_open = true;
+                    }
+                    return Ok(true);
+                }
             }
+            thinking => self.thinking = thinking,
+        }
+        Ok(false)
+    }
+
+    /// Ends the computer's search, whose move is no longer wanted.
+    fn stop_thinking(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thinking = None;
+    }
+
+    fn follow_clock(&mut self) {
+        let over = self.winner() != Square::None;
+        self.session.follow_clock(self.game.board.turn(), self.game.board.moves().len(), over);
+    }
+
+    /// Ends the game for `mark` whose time ran out, or plays a random move for them.
+    fn run_out(&mut self, mark: Square) -> color_eyre::Result<()> {
+        self.stop_thinking();
+        if self.session.run_out(mark) {
+            self.game.managing_game.scores[mark.opponent().index()] += 1;
+        } else if let Some(&mv) = self.game.board.legal_moves().choose(&mut rand::thread_rng()) {
+            self.game.play(mv)?;
+            if self.game.managing_game.winner() != Square::None {
+                self.session.has_menu_open = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the game to the statistics once it is over.
+    fn record_result(&mut self) {
+        let winner = self.winner();
+        if !self.session.needs_recording(winner) {
+            return;
         }
-        frame.render_widget(Paragraph::new(text).centered(), layout[1]);
-        self.game.render(frame, layout[3]);
-
-        if self.has_menu_open {
-            let menu_layout = Layout::default().direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(7),
-                    Constraint::Fill(1)]
-                ).split(Layout::default().direction(Direction::Vertical)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(5),
-                    Constraint::Fill(1)]).split(layout[3])[1]);
-            frame.render_widget(Clear::default(), menu_layout[1]);
-            frame.render_stateful_widget(List::new(["Resume", "Rematch", "Restart", "Menu", "Quit"]).highlight_style(Style::new().add_modifier(Modifier::REVERSED)), menu_layout[1], &mut self.menu_state);
-        } else if self.in_setup {
-            let layout = Layout::default().direction(Direction::Horizontal)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(16),
-                    Constraint::Fill(1)]
-                ).split(Layout::default().direction(Direction::Vertical)
-                .constraints(vec![
-                    Constraint::Fill(1),
-                    Constraint::Length(3),
-                    Constraint::Fill(1)]).split(layout[3])[1]);
-            frame.render_widget(Clear::default(), layout[1]);
-            frame.render_widget(Paragraph::new("Select Game Size\nusing arrow keys\nand hit Enter").centered(), layout[1]);
+        let variant = Variant::Super { size: self.game.board.size() };
+        let result = GameResult::new(variant, &self.player_name(Square::X), &self.player_name(Square::Circle), self.game.board.first(), winner, self.game.board.moves().len());
+        self.session.record(&result);
+    }
+
+    fn settings(&self) -> Vec<String> {
+        let mut settings = vec![format!("Size       {:>3}", self.game.board.size())];
+        settings.extend(self.session.clock_settings());
+        if let Some(budget) = self.computer {
+            settings.push(format!("Think time {:>4}", format!("{}s", budget.time.as_secs_f64())));
+            settings.push(format!("Iterations {:>5}", iterations_name(budget.iterations)));
+        }
+        settings
+    }
+
+    fn change_setting(&mut self, setting: usize, delta: isize) {
+        match setting {
+            0 => self.game.set_size(self.game.board.size().saturating_add_signed(delta)),
+            1 => self.session.step_clock(delta),
+            2 => self.session.on_timeout = self.session.on_timeout.toggle(),
+            3 => if let Some(budget) = &mut self.computer {
+                let index = THINK_TIMES.iter().position(|time| *time == budget.time).unwrap_or(1);
+                budget.time = THINK_TIMES[index.saturating_add_signed(delta).min(THINK_TIMES.len() - 1)];
+            },
+            4 => if let Some(budget) = &mut self.computer {
+                let index = ITERATIONS.iter().position(|iterations| *iterations == budget.iterations).unwrap_or(0);
+                budget.iterations = ITERATIONS[index.saturating_add_signed(delta).min(ITERATIONS.len() - 1)];
+            },
+            _ => {}
         }
     }
 }
 
-impl SuperLocalGame {
-    pub fn new() -> Self {
-        let mut game = SuperGame::new();
-        game.managing_game.show_selector = false;
-        SuperLocalGame { game, has_menu_open: false, menu_state: ListState::default().with_selected(Some(0)), in_setup: true }
+impl Drop for SuperLocalGame {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, TimeControl};
+    use crate::components::harness::Harness;
+
+    fn harness(super_local_game: SuperLocalGame) -> Harness<SuperLocalGame> {
+        Harness::with_size(super_local_game, 90, 30)
+    }
+
+    #[tokio::test]
+    async fn setup_offers_the_computer_budget_only_against_the_computer() {
+        let mut two_players = harness(SuperLocalGame::new());
+        two_players.keys(&["l"]).await;
+        assert!(two_players.contains("Size         4"));
+        assert!(!two_players.contains("Think time"));
+
+        let mut computer = harness(SuperLocalGame::vs_computer());
+        computer.keys(&["j", "j", "j", "l", "j", "l", "l"]).await;
+        assert!(computer.contains("Think time   2s"));
+        assert!(computer.contains("Iterations   10k"));
+        assert_eq!(computer.component.computer, Some(Budget { time: Duration::from_secs(2), iterations: Some(10_000) }));
+    }
+
+    #[tokio::test]
+    async fn moves_are_played_and_taken_back_with_the_keys() {
+        let mut harness = harness(SuperLocalGame::new());
+        harness.keys(&["Enter", "Enter", "Enter"]).await;
+        assert_eq!(harness.component.game.board.moves(), [((1, 1), (1, 1))]);
+        assert!(harness.contains("0 Player1 | Player2 0"));
+        harness.keys(&["u"]).await;
+        assert!(harness.component.game.board.moves().is_empty());
+        harness.keys(&["Ctrl-r"]).await;
+        assert_eq!(harness.component.game.board.moves(), [((1, 1), (1, 1))]);
+    }
+
+    #[tokio::test]
+    async fn back_closes_the_menu_and_clears_the_status() {
+        let mut harness = harness(SuperLocalGame::new());
+        harness.keys(&["Enter", "Esc"]).await;
+        assert!(harness.contains("Resume"));
+        harness.component.session.status = Some("Game saved".to_string());
+        harness.keys(&["j", "Esc"]).await;
+        assert!(!harness.contains("Resume"));
+        assert!(!harness.contains("Game saved"));
+
+        // The menu opens on its first item again.
+        harness.keys(&["Esc", "Enter"]).await;
+        assert!(!harness.component.session.has_menu_open);
+    }
+
+    #[tokio::test]
+    async fn running_out_of_time_loses_and_rematch_keeps_the_score() {
+        let mut harness = harness(SuperLocalGame::new());
+        harness.keys(&["Enter"]).await;
+        harness.component.session.clock = Some(Clock::new(TimeControl::PerMove(Duration::ZERO)));
+        assert!(matches!(harness.component.update().await.unwrap(), Action::Render));
+        assert!(harness.contains("Player1 ran out of time, Player2 wins!"));
+        assert_eq!(harness.component.game.managing_game.scores, [0, 1]);
+        harness.component.update().await.unwrap();
+        assert!(harness.component.session.recorded);
+
+        // Rematch is the sixth item.
+        harness.keys(&["j", "j", "j", "j", "j", "Enter"]).await;
+        assert_eq!(harness.component.winner(), Square::None);
+        assert!(!harness.component.session.recorded);
+        assert!(harness.contains("0 Player1 0.0 | 0.0 Player2 1"));
+    }
+
+    #[tokio::test]
+    async fn saved_games_keep_the_budget_and_the_clock() {
+        let mut harness = harness(SuperLocalGame::vs_computer());
+        harness.keys(&["j", "l", "j", "j", "j", "l", "Enter"]).await;
+        harness.component.game.play(((0, 0), (1, 1))).unwrap();
+        harness.component.save();
+        let path = harness.component.session.save_path.clone().unwrap();
+        let loaded = SuperLocalGame::load(path.clone(), save::load(&path).unwrap()).unwrap();
+        save::delete(&path).unwrap();
+
+        assert_eq!(loaded.game.board.moves(), [((0, 0), (1, 1))]);
+        assert_eq!(loaded.computer, Some(Budget { time: Duration::from_secs(1), iterations: Some(1_000) }));
+        assert_eq!(loaded.session.clock.as_ref().unwrap().control().name(), "1m");
+        assert!(!loaded.session.in_setup);
+        assert!(!loaded.session.has_menu_open);
     }
 }
@@ -0,0 +1,207 @@
+//! User preferences, read from `tic-tac-toe/config.toml` in the XDG config directory on Linux.
+//!
+//! ```toml
+//! [players]
+//! x = "Player1"
+//! o = "Player2"
+//! triangle = "Player3"
+//! block = "Player4"
+//!
+//! [colors]
+//! x = "cyan"
+//! o = "yellow"
+//! triangle = "magenta"
+//! block = "light blue"
+//! selector = "green"
+//! winning_line = "red"
+//! grid = "white"
+//! super_grid = "yellow"
+//!
+//! [defaults]
+//! mode = "normal"
+//! width = 3
+//! height = 3
+//! win_length = 3
+//! super_size = 3
+//! first = "x"
+//! ```
+//!
+//! Missing entries keep their defaults. Colors are named colors, indices like `42` or hex like `#ff8800`.
+//! Key bindings go in a `[keys]` table, see [`crate::keymap`].
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use crate::engine::Square;
+use crate::keymap::{KeyAction, KeyMap};
+
+static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| RwLock::new(Config::default()));
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub players: Players,
+    pub colors: Colors,
+    pub defaults: Defaults,
+    pub keys: KeyMap,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(default)]
+pub struct Players {
+    pub x: String,
+    pub o: String,
+    pub triangle: String,
+    pub block: String,
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Players { x: "Player1".to_string(), o: "Player2".to_string(), triangle: "Player3".to_string(), block: "Player4".to_string() }
+    }
+}
+
+impl Players {
+    pub fn name(&self, mark: Square) -> &str {
+        match mark {
+            Square::Circle => &self.o,
+            Square::Triangle => &self.triangle,
+            Square::Block => &self.block,
+            _ => &self.x,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct Colors {
+    pub x: Color,
+    pub o: Color,
+    pub triangle: Color,
+    pub block: Color,
+    pub selector: Color,
+    pub winning_line: Color,
+    pub grid: Color,
+    pub super_grid: Color,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            x: Color::Cyan,
+            o: Color::Yellow,
+            triangle: Color::Magenta,
+            block: Color::LightBlue,
+            selector: Color::Green,
+            winning_line: Color::Red,
+            grid: Color::White,
+            super_grid: Color::Yellow,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Normal,
+    Super,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum First {
+    X,
+    O,
+}
+
+impl First {
+    pub fn mark(self) -> Square {
+        match self {
+            First::X => Square::X,
+            First::O => Square::Circle,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct Defaults {
+    pub mode: Mode,
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+    pub super_size: usize,
+    pub first: First,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Defaults { mode: Mode::Normal, width: 3, height: 3, win_length: 3, super_size: 3, first: First::X }
+    }
+}
+
+impl Defaults {
+    /// Brings sizes a hand-edited file may get wrong back into the range the game supports.
+    fn clamp(&mut self) {
+        self.width = self.width.max(3);
+        self.height = self.height.max(3);
+        self.win_length = self.win_length.clamp(3, self.width.max(self.height));
+        self.super_size = self.super_size.max(3);
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().ok_or_else(|| eyre!("no config directory found"))?;
+    Ok(config_dir.join("tic-tac-toe").join("config.toml"))
+}
+
+/// Reads the config file, or the defaults if there is none yet.
+pub fn load() -> Result<Config> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let mut config: Config = toml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|error| eyre!("invalid config file {}: {error}", path.display()))?;
+    config.defaults.clamp();
+    Ok(config)
+}
+
+pub fn save(config: &Config) -> Result<PathBuf> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, toml::to_string_pretty(config)?)?;
+    Ok(path)
+}
+
+/// The config in use.
+pub fn get() -> Config {
+    CONFIG.read().unwrap().clone()
+}
+
+/// Colors in use, without copying the rest of the config every frame.
+pub fn colors() -> Colors {
+    CONFIG.read().unwrap().colors
+}
+
+/// Player names in use, looked up whenever they are shown so that renaming players in Settings
+/// applies to games already running.
+pub fn players() -> Players {
+    CONFIG.read().unwrap().players.clone()
+}
+
+/// The action `key_event` is bound to.
+pub fn key_action(key_event: &KeyEvent) -> Option<KeyAction> {
+    CONFIG.read().unwrap().keys.action(key_event)
+}
+
+pub fn set(config: Config) {
+    *CONFIG.write().unwrap() = config;
+}
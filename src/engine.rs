@@ -0,0 +1,791 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// Column and row of a cell, `(0, 0)` being the bottom left corner.
+pub type Position = (usize, usize);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+pub enum Square {
+    Circle,
+    X,
+    /// The third player's mark in games of three or four.
+    Triangle,
+    /// The fourth player's mark, drawn as a square.
+    Block,
+    None,
+    Draw,
+}
+
+/// The players' marks in turn order, a game of `n` players using the first `n`.
+pub const MARKS: [Square; 4] = [Square::X, Square::Circle, Square::Triangle, Square::Block];
+
+impl Square {
+    pub fn opponent(self) -> Square {
+        match self {
+            Square::X => Square::Circle,
+            Square::Circle => Square::X,
+            other => other,
+        }
+    }
+
+    /// Where the mark comes in [`MARKS`], for looking up what belongs to its player.
+    ///
+    /// Panics for `None` and `Draw`, which belong to no player, so callers rule those out first.
+    pub fn index(self) -> usize {
+        MARKS.iter().position(|mark| *mark == self).unwrap_or_else(|| panic!("{self:?} is not a player's mark"))
+    }
+
+    /// The mark to move after this one among `players` players.
+    pub fn next(self, players: usize) -> Square {
+        MARKS[(self.index() + 1) % players]
+    }
+
+    /// The mark to move before this one among `players` players.
+    pub fn previous(self, players: usize) -> Square {
+        MARKS[(self.index() + players - 1) % players]
+    }
+}
+
+/// First and last cell of a completed line.
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct WinningLine {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl WinningLine {
+    /// Unit step from `start` towards `end` on each axis.
+    pub fn direction(&self) -> (i64, i64) {
+        ((self.end.0 as i64 - self.start.0 as i64).signum(), (self.end.1 as i64 - self.start.1 as i64).signum())
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Outcome {
+    InProgress,
+    Win(Square, WinningLine),
+    Draw,
+}
+
+impl Outcome {
+    /// The outcome expressed as a `Square`: the winning mark, `Draw` or `None` while still in progress.
+    pub fn winner(&self) -> Square {
+        match self {
+            Outcome::InProgress => Square::None,
+            Outcome::Win(square, _) => *square,
+            Outcome::Draw => Square::Draw,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum MoveError {
+    OutOfBounds,
+    Occupied,
+    GameOver,
+    WrongBoard,
+    Floating,
+    WrongMark,
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::OutOfBounds => write!(f, "move is outside of the board"),
+            MoveError::Occupied => write!(f, "square is already occupied"),
+            MoveError::GameOver => write!(f, "game is already over"),
+            MoveError::WrongBoard => write!(f, "move has to be made on the board the opponent sent you to"),
+            MoveError::Floating => write!(f, "marks fall down to the lowest empty square of the column"),
+            MoveError::WrongMark => write!(f, "players place their own mark unless the game is wild"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// Variations on the rules of a normal board, which can be combined.
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct Rules {
+    /// Marks fall to the lowest empty square of the column they are dropped in, as in Connect Four.
+    pub gravity: bool,
+    /// Completing a line loses instead of winning, the win going to whoever moves next.
+    pub misere: bool,
+    /// Either player may place X or O, and completing a line of either wins.
+    pub wild: bool,
+}
+
+impl Rules {
+    /// The names of the rules in effect, such as `["gravity"]`.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.gravity {
+            names.push("gravity");
+        }
+        if self.wild {
+            names.push("wild");
+        }
+        if self.misere {
+            names.push("misere");
+        }
+        names
+    }
+
+    /// How a normal game played by these rules is called, such as `Gravity misère` or `Normal`.
+    pub fn mode_name(&self) -> String {
+        let mut words = Vec::new();
+        if self.gravity {
+            words.push("Gravity");
+        }
+        if self.wild {
+            words.push(if words.is_empty() { "Wild" } else { "wild" });
+        }
+        if self.misere {
+            words.push(if words.is_empty() { "Misère" } else { "misère" });
+        }
+        if words.is_empty() { "Normal".to_string() } else { words.join(" ") }
+    }
+
+    /// Turns on the rule called `name`, returning whether there is one.
+    pub fn enable(&mut self, name: &str) -> bool {
+        match name {
+            "gravity" => self.gravity = true,
+            "misere" => self.misere = true,
+            "wild" => self.wild = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    squares: Vec<Vec<Square>>,
+    win_length: usize,
+    rules: Rules,
+    /// How many players take turns, from 2 to 4.
+    players: usize,
+    turn: Square,
+    outcome: Outcome,
+    moves: Vec<Position>,
+    /// Undone moves with the mark that was placed, most recent last.
+    undone: Vec<(Position, Square)>,
+}
+
+impl Board {
+    /// Creates an empty `width`×`height` board on which `win_length` marks in a row win, capped at the longer side.
+    pub fn new(width: usize, height: usize, win_length: usize) -> Self {
+        Board { squares: vec![vec![Square::None; height]; width], win_length: win_length.min(width.max(height)), rules: Rules::default(), players: 2, turn: Square::X, outcome: Outcome::InProgress, moves: Vec::new(), undone: Vec::new() }
+    }
+
+    pub fn width(&self) -> usize {
+        self.squares.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.squares.first().map_or(0, Vec::len)
+    }
+
+    pub fn win_length(&self) -> usize {
+        self.win_length
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Changes the rules, only meaningful before the first move.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    /// Changes how many players take turns, only meaningful before the first move.
+    pub fn set_players(&mut self, players: usize) {
+        self.players = players.clamp(2, MARKS.len());
+    }
+
+    pub fn turn(&self) -> Square {
+        self.turn
+    }
+
+    /// The mark that moved first, assuming the turns went round as they do on a board played on its own.
+    pub fn first(&self) -> Square {
+        MARKS[(self.turn.index() + self.players - self.moves.len() % self.players) % self.players]
+    }
+
+    /// Overrides whose turn it is, used when several boards share one turn order or O starts.
+    pub fn set_turn(&mut self, turn: Square) {
+        self.turn = turn;
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Every move played so far, oldest first.
+    pub fn moves(&self) -> &[Position] {
+        &self.moves
+    }
+
+    pub fn get(&self, position: Position) -> Square {
+        self.squares.get(position.0).and_then(|column| column.get(position.1)).copied().unwrap_or(Square::None)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Position> {
+        if self.outcome != Outcome::InProgress {
+            return Vec::new();
+        }
+        if self.rules.gravity {
+            return (0..self.width()).filter_map(|column| self.landing(column)).collect();
+        }
+        let mut moves = Vec::new();
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                if self.squares[x][y] == Square::None {
+                    moves.push((x, y));
+                }
+            }
+        }
+        moves
+    }
+
+    /// The lowest empty square of `column`, where a mark dropped in it lands with gravity.
+    pub fn landing(&self, column: usize) -> Option<Position> {
+        let y = self.squares.get(column)?.iter().position(|square| *square == Square::None)?;
+        Some((column, y))
+    }
+
+    /// Places the mark of the player to move and passes the turn on.
+    pub fn play(&mut self, mv: Position) -> Result<Outcome, MoveError> {
+        self.play_mark(mv, self.turn)
+    }
+
+    /// Places `mark` for the player to move, which in wild games may be X or O whoever moves.
+    pub fn play_mark(&mut self, mv: Position, mark: Square) -> Result<Outcome, MoveError> {
+        let outcome = self.place(mv, mark)?;
+        self.undone.clear();
+        Ok(outcome)
+    }
+
+    /// Takes back the last move, returning where it was played.
+    pub fn undo(&mut self) -> Option<Position> {
+        let mv = self.moves.pop()?;
+        self.turn = self.turn.previous(self.players);
+        self.undone.push((mv, self.squares[mv.0][mv.1]));
+        self.squares[mv.0][mv.1] = Square::None;
+        self.outcome = Outcome::InProgress;
+        Some(mv)
+    }
+
+    /// Plays the most recently undone move again.
+    pub fn redo(&mut self) -> Option<Outcome> {
+        let (mv, mark) = self.undone.pop()?;
+        self.place(mv, mark).ok()
+    }
+
+    fn place(&mut self, mv: Position, mark: Square) -> Result<Outcome, MoveError> {
+        if self.outcome != Outcome::InProgress {
+            return Err(MoveError::GameOver);
+        }
+        if mv.0 >= self.width() || mv.1 >= self.height() {
+            return Err(MoveError::OutOfBounds);
+        }
+        if self.squares[mv.0][mv.1] != Square::None {
+            return Err(MoveError::Occupied);
+        }
+        if self.rules.gravity && mv.1 > 0 && self.squares[mv.0][mv.1 - 1] == Square::None {
+            return Err(MoveError::Floating);
+        }
+        if mark != self.turn && !(self.rules.wild && matches!(mark, Square::X | Square::Circle)) {
+            return Err(MoveError::WrongMark);
+        }
+
+        self.squares[mv.0][mv.1] = mark;
+        self.moves.push(mv);
+        if let Some(line) = self.line_through(mv) {
+            let winner = if self.rules.misere { self.turn.next(self.players) } else { self.turn };
+            self.outcome = Outcome::Win(winner, line);
+        } else if self.legal_moves().is_empty() {
+            self.outcome = Outcome::Draw;
+        }
+        self.turn = self.turn.next(self.players);
+        Ok(self.outcome)
+    }
+
+    fn line_through(&self, position: Position) -> Option<WinningLine> {
+        let mark = self.get(position);
+        for (dx, dy) in DIRECTIONS {
+            let start = self.run_end(position, mark, (-dx, -dy));
+            let end = self.run_end(position, mark, (dx, dy));
+            let length = (end.0 as i64 - start.0 as i64).abs().max((end.1 as i64 - start.1 as i64).abs()) + 1;
+            if length as usize >= self.win_length {
+                return Some(WinningLine { start, end });
+            }
+        }
+        None
+    }
+
+    /// Last cell reached by walking from `position` in `step` while the squares hold `mark`.
+    fn run_end(&self, position: Position, mark: Square, step: (i64, i64)) -> Position {
+        let mut current = position;
+        loop {
+            let next = (current.0 as i64 + step.0, current.1 as i64 + step.1);
+            if next.0 < 0 || next.1 < 0 || next.0 as usize >= self.width() || next.1 as usize >= self.height() {
+                return current;
+            }
+            let next = (next.0 as usize, next.1 as usize);
+            if self.get(next) != mark {
+                return current;
+            }
+            current = next;
+        }
+    }
+}
+
+/// Board to play on and square within it.
+pub type SuperMove = (Position, Position);
+
+/// Super (ultimate) tic-tac-toe: a board of boards where each move sends the opponent to the
+/// board matching the square just played, unless that board is already decided.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuperBoard {
+    boards: Vec<Vec<Board>>,
+    overall: Board,
+    active: Option<Position>,
+    turn: Square,
+    moves: Vec<SuperMove>,
+    undone: Vec<SuperMove>,
+}
+
+impl SuperBoard {
+    pub fn new(size: usize) -> Self {
+        SuperBoard {
+            boards: vec![vec![Board::new(size, size, size); size]; size],
+            overall: Board::new(size, size, size),
+            active: None,
+            turn: Square::X,
+            moves: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn turn(&self) -> Square {
+        self.turn
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.overall.outcome()
+    }
+
+    /// The mark that moved first.
+    pub fn first(&self) -> Square {
+        if self.moves.len().is_multiple_of(2) { self.turn } else { self.turn.opponent() }
+    }
+
+    /// Lets `first` start instead of X. Only meaningful before the first move.
+    pub fn set_first(&mut self, first: Square) {
+        self.turn = first;
+    }
+
+    /// The board the next move has to be made on, `None` if the player may choose freely.
+    pub fn active(&self) -> Option<Position> {
+        self.active
+    }
+
+    pub fn board(&self, position: Position) -> &Board {
+        &self.boards[position.0][position.1]
+    }
+
+    /// Every move played so far, oldest first.
+    pub fn moves(&self) -> &[SuperMove] {
+        &self.moves
+    }
+
+    /// The board tracking which small boards have been won.
+    pub fn overall(&self) -> &Board {
+        &self.overall
+    }
+
+    pub fn legal_moves(&self) -> Vec<SuperMove> {
+        if self.outcome() != Outcome::InProgress {
+            return Vec::new();
+        }
+        let boards = match self.active {
+            Some(active) => vec![active],
+            None => self.overall.legal_moves(),
+        };
+        boards.into_iter()
+            .flat_map(|board| self.board(board).legal_moves().into_iter().map(move |square| (board, square)))
+            .collect()
+    }
+
+    pub fn play(&mut self, mv: SuperMove) -> Result<Outcome, MoveError> {
+        let outcome = self.place(mv)?;
+        self.undone.clear();
+        Ok(outcome)
+    }
+
+    /// Takes back the last move by replaying all earlier ones, so won boards and the board
+    /// the player is sent to are restored as well.
+    pub fn undo(&mut self) -> Option<SuperMove> {
+        let first = self.first();
+        let mv = self.moves.pop()?;
+        let mut undone = std::mem::take(&mut self.undone);
+        undone.push(mv);
+        let mut replayed = SuperBoard::new(self.size());
+        replayed.set_first(first);
+        for earlier in &self.moves {
+            replayed.place(*earlier).expect("recorded moves are legal");
+        }
+        *self = SuperBoard { undone, ..replayed };
+        Some(mv)
+    }
+
+    /// Plays the most recently undone move again.
+    pub fn redo(&mut self) -> Option<Outcome> {
+        let mv = self.undone.pop()?;
+        self.place(mv).ok()
+    }
+
+    fn place(&mut self, (board, square): SuperMove) -> Result<Outcome, MoveError> {
+        if self.outcome() != Outcome::InProgress {
+            return Err(MoveError::GameOver);
+        }
+        if board.0 >= self.size() || board.1 >= self.size() {
+            return Err(MoveError::OutOfBounds);
+        }
+        if self.active.is_some_and(|active| active != board) {
+            return Err(MoveError::WrongBoard);
+        }
+
+        let small = &mut self.boards[board.0][board.1];
+        small.set_turn(self.turn);
+        if small.play(square)? != Outcome::InProgress {
+            // A decided board, drawn or not, is claimed by whoever finished it.
+            self.overall.set_turn(self.turn);
+            self.overall.play(board)?;
+        }
+        self.active = if self.board(square).outcome() == Outcome::InProgress { Some(square) } else { None };
+        self.turn = self.turn.opponent();
+        self.moves.push((board, square));
+        Ok(self.outcome())
+    }
+}
+
+/// Layer of a cube and square within it.
+pub type CubeMove = (usize, Position);
+
+/// Qubic: tic-tac-toe in an n×n×n cube, played as a stack of n boards. A line of n marks wins,
+/// whether it stays within a layer or runs through all of them, straight or diagonally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CubeBoard {
+    layers: Vec<Board>,
+    turn: Square,
+    /// The winning mark, `Draw` or `None` while still in progress.
+    winner: Square,
+    line: Vec<CubeMove>,
+    moves: Vec<CubeMove>,
+    undone: Vec<CubeMove>,
+}
+
+impl CubeBoard {
+    pub fn new(size: usize) -> Self {
+        CubeBoard {
+            layers: vec![Board::new(size, size, size); size],
+            turn: Square::X,
+            winner: Square::None,
+            line: Vec::new(),
+            moves: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn turn(&self) -> Square {
+        self.turn
+    }
+
+    /// The winning mark, `Draw` or `None` while still in progress.
+    pub fn winner(&self) -> Square {
+        self.winner
+    }
+
+    /// The squares of the completed line, empty while there is none.
+    pub fn line(&self) -> &[CubeMove] {
+        &self.line
+    }
+
+    /// The mark that moved first.
+    pub fn first(&self) -> Square {
+        if self.moves.len().is_multiple_of(2) { self.turn } else { self.turn.opponent() }
+    }
+
+    /// Lets `first` start instead of X. Only meaningful before the first move.
+    pub fn set_first(&mut self, first: Square) {
+        self.turn = first;
+    }
+
+    pub fn layer(&self, layer: usize) -> &Board {
+        &self.layers[layer]
+    }
+
+    pub fn get(&self, (layer, square): CubeMove) -> Square {
+        self.layers.get(layer).map_or(Square::None, |board| board.get(square))
+    }
+
+    /// Every move played so far, oldest first.
+    pub fn moves(&self) -> &[CubeMove] {
+        &self.moves
+    }
+
+    pub fn play(&mut self, mv: CubeMove) -> Result<Square, MoveError> {
+        let winner = self.place(mv)?;
+        self.undone.clear();
+        Ok(winner)
+    }
+
+    /// Takes back the last move, returning where it was played.
+    pub fn undo(&mut self) -> Option<CubeMove> {
+        let mv = self.moves.pop()?;
+        self.layers[mv.0].undo();
+        self.turn = self.turn.opponent();
+        self.winner = Square::None;
+        self.line.clear();
+        self.undone.push(mv);
+        Some(mv)
+    }
+
+    /// Plays the most recently undone move again.
+    pub fn redo(&mut self) -> Option<Square> {
+        let mv = self.undone.pop()?;
+        self.place(mv).ok()
+    }
+
+    fn place(&mut self, (layer, square): CubeMove) -> Result<Square, MoveError> {
+        if self.winner != Square::None {
+            return Err(MoveError::GameOver);
+        }
+        let board = self.layers.get_mut(layer).ok_or(MoveError::OutOfBounds)?;
+        board.set_turn(self.turn);
+        board.play(square)?;
+        self.moves.push((layer, square));
+        if let Some(line) = lines(self.size()).into_iter().find(|line| line.contains(&(layer, square)) && line.iter().all(|mv| self.get(*mv) == self.turn)) {
+            self.winner = self.turn;
+            self.line = line;
+        } else if self.moves.len() == self.size().pow(3) {
+            self.winner = Square::Draw;
+        }
+        self.turn = self.turn.opponent();
+        Ok(self.winner)
+    }
+}
+
+/// Every line of `size` squares in a cube of that size, 76 of them in the 4×4×4 cube.
+pub fn lines(size: usize) -> Vec<Vec<CubeMove>> {
+    let size = size as i64;
+    let inside = |(x, y, z): (i64, i64, i64)| (0..size).contains(&x) && (0..size).contains(&y) && (0..size).contains(&z);
+    // One direction of each pair of opposites: 3 along the axes, 6 diagonals across two of them
+    // and 4 space diagonals across all three.
+    let mut directions = Vec::new();
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                if (dx, dy, dz) > (0, 0, 0) {
+                    directions.push((dx, dy, dz));
+                }
+            }
+        }
+    }
+    let mut lines = Vec::new();
+    for (dx, dy, dz) in directions {
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    // A line is found once, from the end with no square before it.
+                    let end = (x + dx * (size - 1), y + dy * (size - 1), z + dz * (size - 1));
+                    if inside((x - dx, y - dy, z - dz)) || !inside(end) {
+                        continue;
+                    }
+                    lines.push((0..size).map(|i| ((z + dz * i) as usize, ((x + dx * i) as usize, (y + dy * i) as usize))).collect());
+                }
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board with `moves` played in turn.
+    fn played(mut board: Board, moves: &[Position]) -> Board {
+        for mv in moves {
+            board.play(*mv).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn marks_go_round_among_the_players() {
+        assert_eq!(Square::X.next(2), Square::Circle);
+        assert_eq!(Square::Circle.next(2), Square::X);
+        assert_eq!(Square::Circle.next(3), Square::Triangle);
+        assert_eq!(Square::X.previous(4), Square::Block);
+        assert_eq!(Square::Block.index(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Draw is not a player's mark")]
+    fn only_player_marks_have_an_index() {
+        Square::Draw.index();
+    }
+
+    #[test]
+    fn a_row_wins_and_ends_the_game() {
+        let mut board = played(Board::new(3, 3, 3), &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(board.legal_moves().len(), 5);
+        let outcome = board.play((2, 0)).unwrap();
+        assert_eq!(outcome, Outcome::Win(Square::X, WinningLine { start: (0, 0), end: (2, 0) }));
+        assert!(board.legal_moves().is_empty());
+        assert_eq!(board.play((2, 2)), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn full_board_without_a_line_is_a_draw() {
+        let board = played(Board::new(3, 3, 3), &[(0, 0), (1, 1), (2, 2), (0, 1), (2, 1), (2, 0), (0, 2), (1, 2), (1, 0)]);
+        assert_eq!(board.outcome(), Outcome::Draw);
+    }
+
+    #[test]
+    fn moves_off_the_board_or_on_a_mark_are_refused() {
+        let mut board = played(Board::new(3, 3, 3), &[(1, 1)]);
+        assert_eq!(board.play((3, 0)), Err(MoveError::OutOfBounds));
+        assert_eq!(board.play((0, 3)), Err(MoveError::OutOfBounds));
+        assert_eq!(board.play((1, 1)), Err(MoveError::Occupied));
+        assert_eq!(board.turn(), Square::Circle);
+        assert_eq!(board.moves(), [(1, 1)]);
+    }
+
+    #[test]
+    fn only_wild_games_let_players_place_the_other_mark() {
+        let mut board = Board::new(3, 3, 3);
+        assert_eq!(board.play_mark((0, 0), Square::Circle), Err(MoveError::WrongMark));
+        board.set_rules(Rules { wild: true, ..Rules::default() });
+        board.set_players(3);
+        board.play_mark((0, 0), Square::Circle).unwrap();
+        assert_eq!(board.get((0, 0)), Square::Circle);
+        assert_eq!(board.turn(), Square::Circle);
+        assert_eq!(board.play_mark((1, 0), Square::Triangle), Err(MoveError::WrongMark));
+    }
+
+    #[test]
+    fn misere_completing_a_line_hands_the_win_to_the_next_player() {
+        let mut board = Board::new(3, 3, 3);
+        board.set_rules(Rules { misere: true, ..Rules::default() });
+        let board = played(board, &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)]);
+        assert_eq!(board.outcome().winner(), Square::Circle);
+    }
+
+    #[test]
+    fn gravity_drops_marks_to_the_lowest_empty_square() {
+        let mut board = Board::new(3, 3, 3);
+        board.set_rules(Rules { gravity: true, ..Rules::default() });
+        assert_eq!(board.legal_moves(), [(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(board.play((0, 1)), Err(MoveError::Floating));
+        board.play((0, 0)).unwrap();
+        assert_eq!(board.legal_moves(), [(0, 1), (1, 0), (2, 0)]);
+        assert_eq!(board.landing(0), Some((0, 1)));
+        board.play((0, 1)).unwrap();
+        board.play((0, 2)).unwrap();
+        assert_eq!(board.landing(0), None);
+    }
+
+    #[test]
+    fn undo_takes_back_the_win_and_redo_plays_it_again() {
+        let mut board = played(Board::new(3, 3, 3), &[(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)]);
+        assert_eq!(board.undo(), Some((2, 0)));
+        assert_eq!(board.outcome(), Outcome::InProgress);
+        assert_eq!(board.get((2, 0)), Square::None);
+        assert_eq!(board.turn(), Square::X);
+        assert_eq!(board.redo().map(|outcome| outcome.winner()), Some(Square::X));
+        assert_eq!(board.redo(), None);
+
+        // A new move forgets what was undone.
+        board.undo();
+        board.play((2, 2)).unwrap();
+        assert_eq!(board.redo(), None);
+        assert_eq!(Board::new(3, 3, 3).undo(), None);
+    }
+
+    #[test]
+    fn super_moves_go_to_the_board_the_opponent_was_sent_to() {
+        let mut board = SuperBoard::new(3);
+        assert_eq!(board.legal_moves().len(), 81);
+        board.play(((1, 1), (0, 2))).unwrap();
+        assert_eq!(board.active(), Some((0, 2)));
+        assert!(board.legal_moves().iter().all(|(small, _)| *small == (0, 2)));
+        assert_eq!(board.play(((1, 1), (0, 0))), Err(MoveError::WrongBoard));
+        assert_eq!(board.play(((3, 0), (0, 0))), Err(MoveError::OutOfBounds));
+        assert_eq!(board.play(((0, 2), (3, 0))), Err(MoveError::OutOfBounds));
+        board.play(((0, 2), (1, 1))).unwrap();
+        assert_eq!(board.play(((1, 1), (0, 2))), Err(MoveError::Occupied));
+    }
+
+    #[test]
+    fn super_undo_restores_won_boards_and_the_active_board() {
+        let mut board = SuperBoard::new(3);
+        for mv in [
+            ((0, 0), (0, 0)), ((0, 0), (1, 1)), ((1, 1), (0, 0)), ((0, 0), (2, 2)), ((2, 2), (0, 0)),
+            ((0, 0), (1, 2)), ((1, 2), (0, 0)), ((0, 0), (0, 1)), ((0, 1), (0, 0)), ((0, 0), (1, 0)),
+        ] {
+            board.play(mv).unwrap();
+        }
+        assert_eq!(board.overall().get((0, 0)), Square::Circle);
+        assert_eq!(board.active(), Some((1, 0)));
+        assert_eq!(board.undo(), Some(((0, 0), (1, 0))));
+        assert_eq!(board.overall().get((0, 0)), Square::None);
+        assert_eq!(board.active(), Some((0, 0)));
+        assert_eq!(board.turn(), Square::Circle);
+        assert_eq!(board.redo(), Some(Outcome::InProgress));
+        assert_eq!(board.overall().get((0, 0)), Square::Circle);
+
+        // A won board sends nobody there and takes no more moves.
+        board.play(((1, 0), (0, 0))).unwrap();
+        assert_eq!(board.active(), None);
+        assert_eq!(board.play(((0, 0), (2, 0))), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn cube_lines_win_across_layers() {
+        let mut board = CubeBoard::new(3);
+        for mv in [(0, (0, 0)), (0, (1, 0)), (1, (1, 1)), (0, (2, 0))] {
+            assert_eq!(board.play(mv), Ok(Square::None));
+        }
+        assert_eq!(board.play((0, (0, 0))), Err(MoveError::Occupied));
+        assert_eq!(board.play((3, (0, 0))), Err(MoveError::OutOfBounds));
+        assert_eq!(board.play((0, (3, 0))), Err(MoveError::OutOfBounds));
+        assert_eq!(board.play((2, (2, 2))), Ok(Square::X));
+        assert_eq!(board.line(), [(0, (0, 0)), (1, (1, 1)), (2, (2, 2))]);
+        assert_eq!(board.play((2, (0, 0))), Err(MoveError::GameOver));
+
+        assert_eq!(board.undo(), Some((2, (2, 2))));
+        assert_eq!(board.winner(), Square::None);
+        assert!(board.line().is_empty());
+        assert_eq!(board.turn(), Square::X);
+        assert_eq!(board.redo(), Some(Square::X));
+    }
+}
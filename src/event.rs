@@ -0,0 +1,70 @@
+//! Everything the app reacts to, merged into one channel: terminal input, timer ticks and
+//! actions sent by background tasks.
+
+use std::io;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use futures::StreamExt;
+use ratatui::crossterm::event::EventStream;
+use tokio::sync::mpsc;
+use crate::action::Action;
+
+/// How often components get to update on their own, e.g. to step a replay.
+const TICK_RATE: Duration = Duration::from_millis(50);
+
+/// Created up front so that events sent before the app listens, e.g. by a game started from
+/// the command line, are kept until it does.
+static CHANNEL: LazyLock<Channel> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    Channel { sender, receiver: Mutex::new(Some(receiver)) }
+});
+
+struct Channel {
+    sender: mpsc::UnboundedSender<Event>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<Event>>>,
+}
+
+pub enum Event {
+    Input(ratatui::crossterm::event::Event),
+    InputError(io::Error),
+    Tick,
+    Action(Action),
+}
+
+/// Starts reading terminal input and ticking, returning where the events arrive. Only the first
+/// call gets the events.
+pub fn listen() -> Option<mpsc::UnboundedReceiver<Event>> {
+    let receiver = CHANNEL.receiver.lock().unwrap().take()?;
+    let input = CHANNEL.sender.clone();
+    tokio::spawn(async move {
+        let mut events = EventStream::new();
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Ok(event) => Event::Input(event),
+                Err(error) => Event::InputError(error),
+            };
+            if input.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    let ticks = CHANNEL.sender.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_RATE);
+        while ticks.send(Event::Tick).is_ok() {
+            interval.tick().await;
+        }
+    });
+    Some(receiver)
+}
+
+/// Hands `action` to the app from a background task.
+pub fn send(action: Action) {
+    let _ = CHANNEL.sender.send(Event::Action(action));
+}
+
+/// Lets the open component update right away instead of at the next tick, for background
+/// tasks that finished something it is waiting for.
+pub fn wake() {
+    let _ = CHANNEL.sender.send(Event::Tick);
+}
@@ -0,0 +1,332 @@
+//! Key bindings, configured in the `[keys]` table of the config file.
+//!
+//! ```toml
+//! [keys]
+//! up = ["k", "Up"]
+//! confirm = ["Enter", "Space"]
+//! redo = ["Ctrl-r"]
+//! numpad = true
+//! ```
+//!
+//! Keys are written as a character or a name such as `Enter`, `Esc`, `Backspace`, `Delete`, `Tab`,
+//! `Space`, `Home`, `End`, `PageUp`, `PageDown`, an arrow like `Left` or a function key like `F5`,
+//! optionally prefixed with `Ctrl-` or `Alt-`. Actions left out keep their default keys. With
+//! `numpad` on, 1–9 place a mark on a 3×3 board in the cell laid out like a numeric keypad.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::engine::Position;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Quit,
+    Undo,
+    Redo,
+    Delete,
+    First,
+    Last,
+    Play,
+    Faster,
+    Slower,
+    NextLayer,
+    PreviousLayer,
+    SwitchMark,
+    Help,
+    /// A cell of a 3×3 board picked with the numeric keypad.
+    Cell(Position),
+}
+
+impl KeyAction {
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Up => "Up",
+            KeyAction::Down => "Down",
+            KeyAction::Left => "Left",
+            KeyAction::Right => "Right",
+            KeyAction::Confirm => "Play / choose",
+            KeyAction::Back => "Menu / back",
+            KeyAction::Quit => "Quit",
+            KeyAction::Undo => "Undo",
+            KeyAction::Redo => "Redo",
+            KeyAction::Delete => "Delete save",
+            KeyAction::First => "Replay start",
+            KeyAction::Last => "Replay end",
+            KeyAction::Play => "Replay play / pause",
+            KeyAction::Faster => "Replay faster",
+            KeyAction::Slower => "Replay slower",
+            KeyAction::NextLayer => "Next 3D layer",
+            KeyAction::PreviousLayer => "Previous 3D layer",
+            KeyAction::SwitchMark => "Wild: switch mark",
+            KeyAction::Help => "Help",
+            KeyAction::Cell(_) => "Numpad square",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn matches(&self, key_event: &KeyEvent) -> bool {
+        let mut modifiers = key_event.modifiers;
+        if let KeyCode::Char(_) = key_event.code {
+            // Upper case letters and symbols arrive with shift held, which is already in the character.
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == key_event.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(stripped) = rest.strip_prefix("Ctrl-").filter(|stripped| !stripped.is_empty()) {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("Alt-").filter(|stripped| !stripped.is_empty()) {
+                modifiers |= KeyModifiers::ALT;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+        let code = match rest {
+            "Enter" => KeyCode::Enter,
+            "Esc" => KeyCode::Esc,
+            "Backspace" => KeyCode::Backspace,
+            "Delete" => KeyCode::Delete,
+            "Tab" => KeyCode::Tab,
+            "Space" => KeyCode::Char(' '),
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(char), None) => KeyCode::Char(char),
+                    _ => match rest.strip_prefix('F').and_then(|number| number.parse().ok()) {
+                        Some(number) => KeyCode::F(number),
+                        None => return Err(format!("unknown key \"{text}\"")),
+                    },
+                }
+            }
+        };
+        Ok(Key { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(char) => write!(f, "{char}"),
+            KeyCode::F(number) => write!(f, "F{number}"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(default)]
+pub struct KeyMap {
+    pub up: Vec<Key>,
+    pub down: Vec<Key>,
+    pub left: Vec<Key>,
+    pub right: Vec<Key>,
+    pub confirm: Vec<Key>,
+    pub back: Vec<Key>,
+    pub quit: Vec<Key>,
+    pub undo: Vec<Key>,
+    pub redo: Vec<Key>,
+    pub delete: Vec<Key>,
+    pub first: Vec<Key>,
+    pub last: Vec<Key>,
+    pub play: Vec<Key>,
+    pub faster: Vec<Key>,
+    pub slower: Vec<Key>,
+    pub next_layer: Vec<Key>,
+    pub previous_layer: Vec<Key>,
+    pub switch_mark: Vec<Key>,
+    pub help: Vec<Key>,
+    pub numpad: bool,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.parse().expect("default keys are valid")).collect();
+        KeyMap {
+            up: keys(&["k", "Up"]),
+            down: keys(&["j", "Down"]),
+            left: keys(&["h", "Left"]),
+            right: keys(&["l", "Right"]),
+            confirm: keys(&["Enter"]),
+            back: keys(&["Esc", "Backspace"]),
+            quit: keys(&["q"]),
+            undo: keys(&["u"]),
+            redo: keys(&["Ctrl-r"]),
+            delete: keys(&["Delete"]),
+            first: keys(&["Home", "g"]),
+            last: keys(&["End", "G"]),
+            play: keys(&["Space"]),
+            faster: keys(&["+"]),
+            slower: keys(&["-"]),
+            next_layer: keys(&["]"]),
+            previous_layer: keys(&["["]),
+            switch_mark: keys(&["m"]),
+            help: keys(&["?"]),
+            numpad: false,
+        }
+    }
+}
+
+impl KeyMap {
+    /// The action bound to `key_event`. Bindings are checked in the order of the fields, so a
+    /// key bound twice does what the earlier action does.
+    pub fn action(&self, key_event: &KeyEvent) -> Option<KeyAction> {
+        if self.numpad {
+            if let KeyCode::Char(digit @ '1'..='9') = key_event.code {
+                let index = digit as usize - '1' as usize;
+                return Some(KeyAction::Cell((index % 3, index / 3)));
+            }
+        }
+        self.bindings().into_iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(key_event)))
+            .map(|(action, _)| action)
+    }
+
+    /// Every action with the keys bound to it.
+    pub fn bindings(&self) -> [(KeyAction, &[Key]); 19] {
+        [
+            (KeyAction::Up, &self.up),
+            (KeyAction::Down, &self.down),
+            (KeyAction::Left, &self.left),
+            (KeyAction::Right, &self.right),
+            (KeyAction::Confirm, &self.confirm),
+            (KeyAction::Back, &self.back),
+            (KeyAction::Quit, &self.quit),
+            (KeyAction::Undo, &self.undo),
+            (KeyAction::Redo, &self.redo),
+            (KeyAction::Delete, &self.delete),
+            (KeyAction::First, &self.first),
+            (KeyAction::Last, &self.last),
+            (KeyAction::Play, &self.play),
+            (KeyAction::Faster, &self.faster),
+            (KeyAction::Slower, &self.slower),
+            (KeyAction::NextLayer, &self.next_layer),
+            (KeyAction::PreviousLayer, &self.previous_layer),
+            (KeyAction::SwitchMark, &self.switch_mark),
+            (KeyAction::Help, &self.help),
+        ]
+    }
+}
+
+/// The character `key_event` types into a text field, which takes precedence over key bindings.
+pub fn typed_char(key_event: &KeyEvent) -> Option<char> {
+    match key_event.code {
+        KeyCode::Char(char) if !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(char),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        KeyEvent::new(key.code, key.modifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn keys_are_written_back_as_they_were_read() {
+        for text in ["k", "G", "+", "Enter", "Esc", "Backspace", "Delete", "Tab", "Space", "Home", "End", "PageUp", "PageDown", "Up", "Down", "Left", "Right", "F5", "Ctrl-r", "Alt-x", "Ctrl-Alt-Enter", "Ctrl--"] {
+            assert_eq!(text.parse::<Key>().unwrap().to_string(), text);
+        }
+        assert_eq!("Space".parse::<Key>(), " ".parse::<Key>());
+        for text in ["", "Ctrl-", "Enterr", "Fx", "Shift-a"] {
+            assert!(text.parse::<Key>().is_err(), "{text:?} parsed");
+        }
+    }
+
+    #[test]
+    fn modifiers_have_to_match_except_shift_on_characters() {
+        let key = |text: &str| text.parse::<Key>().unwrap();
+        assert!(key("G").matches(&event(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(key("r").matches(&event(KeyCode::Char('r'), KeyModifiers::NONE)));
+        assert!(!key("r").matches(&event(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert!(key("Ctrl-r").matches(&event(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert!(!key("Ctrl-r").matches(&event(KeyCode::Char('r'), KeyModifiers::CONTROL | KeyModifiers::ALT)));
+        assert!(!key("Up").matches(&event(KeyCode::Up, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn numpad_digits_pick_the_cell_laid_out_like_the_keypad() {
+        let digit = |char| event(KeyCode::Char(char), KeyModifiers::NONE);
+        let mut key_map = KeyMap::default();
+        assert_eq!(key_map.action(&digit('7')), None);
+
+        key_map.numpad = true;
+        assert_eq!(key_map.action(&digit('1')), Some(KeyAction::Cell((0, 0))));
+        assert_eq!(key_map.action(&digit('3')), Some(KeyAction::Cell((2, 0))));
+        assert_eq!(key_map.action(&digit('5')), Some(KeyAction::Cell((1, 1))));
+        assert_eq!(key_map.action(&digit('7')), Some(KeyAction::Cell((0, 2))));
+        assert_eq!(key_map.action(&digit('9')), Some(KeyAction::Cell((2, 2))));
+        assert_eq!(key_map.action(&digit('0')), None);
+        assert_eq!(key_map.action(&digit('k')), Some(KeyAction::Up));
+    }
+
+    #[test]
+    fn only_plain_and_shifted_characters_are_typed() {
+        assert_eq!(typed_char(&event(KeyCode::Char('a'), KeyModifiers::NONE)), Some('a'));
+        assert_eq!(typed_char(&event(KeyCode::Char(':'), KeyModifiers::SHIFT)), Some(':'));
+        assert_eq!(typed_char(&event(KeyCode::Char('r'), KeyModifiers::CONTROL)), None);
+        assert_eq!(typed_char(&event(KeyCode::Enter, KeyModifiers::NONE)), None);
+    }
+}
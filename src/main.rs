@@ -1,11 +1,24 @@
 mod app;
+mod cli;
+mod clock;
+mod config;
+mod keymap;
 mod components;
 mod action;
+mod engine;
+mod event;
+mod ai;
+mod net;
+mod notation;
+mod save;
+mod stats;
 use color_eyre::Result;
+use clap::Parser;
 use crate::app::App;
+use crate::cli::Cli;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    App::start().await
+    App::start(Cli::parse()).await
 }
@@ -0,0 +1,211 @@
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use std::fmt::{Display, Formatter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TryRecvError;
+use crate::event;
+use crate::net::protocol::{Message, Settings, PROTOCOL_VERSION};
+
+pub mod discovery;
+pub mod protocol;
+
+/// Longest line a peer may send, far more than any message needs. Anything longer is treated as
+/// a protocol violation rather than read into memory.
+const MAX_LINE: u64 = 4096;
+
+/// An established connection to the other player. Reading and writing happen on background
+/// tasks so the UI can poll for messages without blocking.
+pub struct Connection {
+    outgoing: mpsc::UnboundedSender<Message>,
+    incoming: mpsc::UnboundedReceiver<Message>,
+}
+
+impl Connection {
+    /// Waits for one player to join on `address` and sends them the game settings.
+    pub async fn host(address: &str, settings: Settings) -> Result<Connection> {
+        Self::accept(TcpListener::bind(address).await?, settings).await
+    }
+
+    async fn accept(listener: TcpListener, settings: Settings) -> Result<Connection> {
+        let (stream, _) = listener.accept().await?;
+        let (read, mut write) = stream.into_split();
+        let mut read = BufReader::new(read);
+
+        match read_message(&mut read).await? {
+            Message::Hello { version } if version == PROTOCOL_VERSION => {}
+            Message::Hello { version } => {
+                let reason = format!("host speaks protocol version {PROTOCOL_VERSION}, you speak {version}");
+                write_message(&mut write, &Message::Error { reason: reason.clone() }).await?;
+                bail!(reason);
+            }
+            other => bail!("expected hello, got {other:?}"),
+        }
+        write_message(&mut write, &Message::Welcome { version: PROTOCOL_VERSION, settings }).await?;
+        Ok(Self::spawn(read, write))
+    }
+
+    /// Connects to a host and returns the settings of the game it offers.
+    pub async fn join(address: &str) -> Result<(Connection, Settings)> {
+        let (read, mut write) = TcpStream::connect(address).await?.into_split();
+        let mut read = BufReader::new(read);
+
+        write_message(&mut write, &Message::Hello { version: PROTOCOL_VERSION }).await?;
+        let settings = match read_message(&mut read).await? {
+            Message::Welcome { version, settings } if version == PROTOCOL_VERSION => settings,
+            Message::Welcome { version, .. } => bail!("host speaks protocol version {version}, you speak {PROTOCOL_VERSION}"),
+            Message::Error { reason } => bail!(reason),
+            other => bail!("expected welcome, got {other:?}"),
+        };
+        if !settings.is_valid() {
+            bail!("host offered an unplayable board: {settings:?}");
+        }
+        Ok((Self::spawn(read, write), settings))
+    }
+
+    pub fn send(&self, message: Message) {
+        // The writer only stops once the connection is gone, which `try_recv` reports.
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Next message from the other player, if one arrived. A lost connection is reported as `Disconnect`.
+    pub fn try_recv(&mut self) -> Option<Message> {
+        match self.incoming.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Message::Disconnect),
+        }
+    }
+
+    /// A host and a joiner connected over localhost, for tests.
+    #[cfg(test)]
+    pub async fn pair(settings: Settings) -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let host = tokio::spawn(Self::accept(listener, settings));
+        let (joiner, offered) = Self::join(&address).await.unwrap();
+        assert_eq!(offered, settings);
+        (host.await.unwrap().unwrap(), joiner)
+    }
+
+    /// Waits for the next message, for tests.
+    #[cfg(test)]
+    pub async fn recv(&mut self) -> Message {
+        for _ in 0..200 {
+            if let Some(message) = self.try_recv() {
+                return message;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        panic!("no message arrived");
+    }
+
+    fn spawn(mut read: BufReader<OwnedReadHalf>, mut write: OwnedWriteHalf) -> Connection {
+        let (outgoing, mut outgoing_receiver) = mpsc::unbounded_channel::<Message>();
+        let (incoming_sender, incoming) = mpsc::unbounded_channel();
+        let replies = outgoing.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = outgoing_receiver.recv().await {
+                let closing = matches!(message, Message::Disconnect | Message::Error { .. });
+                if write_message(&mut write, &message).await.is_err() || closing {
+                    break;
+                }
+            }
+            let _ = write.shutdown().await;
+        });
+        tokio::spawn(async move {
+            loop {
+                let message = match read_message(&mut read).await {
+                    Ok(message) => message,
+                    Err(error) if error.is::<TooLong>() => {
+                        // Told to both sides, the peer before the connection closes and the game as
+                        // the reason it did.
+                        let reason = error.to_string();
+                        let _ = replies.send(Message::Error { reason: reason.clone() });
+                        Message::Error { reason }
+                    }
+                    Err(_) => break,
+                };
+                let closing = matches!(message, Message::Disconnect | Message::Error { .. });
+                if incoming_sender.send(message).is_err() || closing {
+                    break;
+                }
+                event::wake();
+            }
+            // Dropping the sender is how a lost connection shows up in `try_recv`.
+            drop(incoming_sender);
+            event::wake();
+        });
+
+        Connection { outgoing, incoming }
+    }
+}
+
+/// A line longer than [`MAX_LINE`] from the peer.
+#[derive(Debug)]
+struct TooLong;
+
+impl Display for TooLong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "opponent sent a message longer than {MAX_LINE} bytes")
+    }
+}
+
+impl std::error::Error for TooLong {}
+
+async fn read_message(read: &mut BufReader<OwnedReadHalf>) -> Result<Message> {
+    let mut line = String::new();
+    let length = read.take(MAX_LINE + 1).read_line(&mut line).await?;
+    if length == 0 {
+        return Err(eyre!("connection closed"));
+    }
+    if length as u64 > MAX_LINE {
+        return Err(TooLong.into());
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+async fn write_message(write: &mut OwnedWriteHalf, message: &Message) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    write.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn handshake_and_moves_reach_the_other_side() {
+        let settings = Settings::Super { size: 4 };
+        let (host, mut joiner) = Connection::pair(settings).await;
+        host.send(Message::Move { board: Some((1, 1)), square: (0, 3) });
+        assert_eq!(joiner.recv().await, Message::Move { board: Some((1, 1)), square: (0, 3) });
+
+        host.send(Message::Disconnect);
+        assert_eq!(joiner.recv().await, Message::Disconnect);
+    }
+
+    #[tokio::test]
+    async fn overlong_lines_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = tokio::spawn(Connection::accept(listener, Settings::default()));
+        let (read, mut write) = TcpStream::connect(address).await.unwrap().into_split();
+        let mut read = BufReader::new(read);
+        write_message(&mut write, &Message::Hello { version: PROTOCOL_VERSION }).await.unwrap();
+        assert!(matches!(read_message(&mut read).await.unwrap(), Message::Welcome { .. }));
+        let mut host = host.await.unwrap().unwrap();
+
+        write.write_all(&[b' '; MAX_LINE as usize * 2]).await.unwrap();
+        let Message::Error { reason } = host.recv().await else {
+            panic!("the host accepted an endless line");
+        };
+        assert!(reason.contains("longer than"));
+        assert_eq!(read_message(&mut read).await.unwrap(), Message::Error { reason });
+    }
+}
@@ -0,0 +1,115 @@
+//! LAN game discovery.
+//!
+//! While waiting for an opponent, a host broadcasts a UDP datagram to port 7001 every second
+//! containing one JSON object such as
+//! `{"version":1,"name":"alice","port":7000,"settings":{"mode":"normal","width":3,"height":3,"win_length":3}}`.
+//! Players looking for games listen on that port and connect to the sender's address at `port`.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use crate::event;
+use crate::net::protocol::{Settings, PROTOCOL_VERSION};
+
+pub const DISCOVERY_PORT: u16 = 7001;
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+/// Games not heard from for this long are considered gone.
+const EXPIRY: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Announcement {
+    pub version: u32,
+    pub name: String,
+    pub port: u16,
+    pub settings: Settings,
+}
+
+/// Broadcasts `announcement` until the returned task is aborted.
+pub fn announce(announcement: Announcement) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let Ok(socket) = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await else {
+            return;
+        };
+        let Ok(datagram) = serde_json::to_vec(&announcement) else {
+            return;
+        };
+        if socket.set_broadcast(true).is_err() {
+            return;
+        }
+        loop {
+            // A missing network is not worth interrupting the host for; keep trying.
+            let _ = socket.send_to(&datagram, (Ipv4Addr::BROADCAST, DISCOVERY_PORT)).await;
+            tokio::time::sleep(ANNOUNCE_INTERVAL).await;
+        }
+    })
+}
+
+/// A game seen on the network, reachable at `address`.
+#[derive(Clone, Debug)]
+pub struct LanGame {
+    pub address: SocketAddr,
+    pub announcement: Announcement,
+    last_seen: Instant,
+}
+
+/// Collects announcements of games on the local network.
+pub struct Browser {
+    receiver: mpsc::UnboundedReceiver<LanGame>,
+    task: JoinHandle<()>,
+    games: HashMap<SocketAddr, LanGame>,
+}
+
+impl Browser {
+    pub async fn listen() -> Result<Browser> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)).await?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            let mut buffer = [0; 1024];
+            while let Ok((length, source)) = socket.recv_from(&mut buffer).await {
+                let Ok(announcement) = serde_json::from_slice::<Announcement>(&buffer[..length]) else {
+                    continue;
+                };
+                if announcement.version != PROTOCOL_VERSION || !announcement.settings.is_valid() {
+                    continue;
+                }
+                let address = SocketAddr::new(source.ip(), announcement.port);
+                if sender.send(LanGame { address, announcement, last_seen: Instant::now() }).is_err() {
+                    break;
+                }
+                event::wake();
+            }
+        });
+        Ok(Browser { receiver, task, games: HashMap::new() })
+    }
+
+    /// Takes in new announcements and forgets games that stopped announcing. Returns whether the
+    /// list of games changed.
+    pub fn refresh(&mut self) -> bool {
+        let known = self.games.len();
+        let mut changed = false;
+        while let Ok(game) = self.receiver.try_recv() {
+            let announcement = game.announcement.clone();
+            changed |= self.games.insert(game.address, game).is_none_or(|old| old.announcement != announcement);
+        }
+        self.games.retain(|_, game| game.last_seen.elapsed() < EXPIRY);
+        changed || self.games.len() != known
+    }
+
+    /// Known games, sorted by address so the list does not jump around.
+    pub fn games(&self) -> Vec<&LanGame> {
+        let mut games: Vec<&LanGame> = self.games.values().collect();
+        games.sort_by_key(|game| game.address);
+        games
+    }
+}
+
+impl Drop for Browser {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,67 @@
+//! Wire protocol for online games.
+//!
+//! Peers talk over a single TCP connection. Every message is one JSON object on its own line,
+//! tagged by its `type` field. The host plays X and moves first.
+//!
+//! 1. The joining client sends `{"type":"hello","version":1}`.
+//! 2. The host answers with the game settings, e.g.
+//!    `{"type":"welcome","version":1,"settings":{"mode":"normal","width":3,"height":3,"win_length":3}}`
+//!    or `{"type":"welcome","version":1,"settings":{"mode":"super","size":3}}`.
+//!    If the versions differ it sends an `error` and closes the connection instead.
+//! 3. Both sides then exchange:
+//!    - `{"type":"move","square":[x,y]}` for the normal game, or
+//!      `{"type":"move","board":[x,y],"square":[x,y]}` for super games, `[0,0]` being the bottom left;
+//!    - `{"type":"resign"}`;
+//!    - `{"type":"rematch_offer"}` and `{"type":"rematch_accept"}`, starting a fresh board once accepted;
+//!    - `{"type":"error","reason":"..."}` when a peer breaks the rules, followed by closing the connection;
+//!    - `{"type":"disconnect"}` before leaving.
+//!
+//! Every move is checked against the rules on arrival, so a modified client cannot cheat.
+
+use serde::{Deserialize, Serialize};
+use crate::engine::Position;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const MAX_SIZE: usize = 32;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Settings {
+    Normal { width: usize, height: usize, win_length: usize },
+    Super { size: usize },
+}
+
+impl Settings {
+    /// Whether a board of this shape can be played, guarding against absurd sizes from a remote host.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Settings::Normal { width, height, win_length } => (3..=MAX_SIZE).contains(&width)
+                && (3..=MAX_SIZE).contains(&height)
+                && (3..=width.max(height)).contains(&win_length),
+            Settings::Super { size } => (3..=MAX_SIZE).contains(&size),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::Normal { width: 3, height: 3, win_length: 3 }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Hello { version: u32 },
+    Welcome { version: u32, settings: Settings },
+    Move {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        board: Option<Position>,
+        square: Position,
+    },
+    Resign,
+    RematchOffer,
+    RematchAccept,
+    Error { reason: String },
+    Disconnect,
+}
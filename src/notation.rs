@@ -0,0 +1,512 @@
+//! A plain text record of a game, modelled on chess PGN.
+//!
+//! ```text
+//! [Mode "normal"]
+//! [Size "3x3"]
+//! [WinLength "3"]
+//! [X "Player1"]
+//! [O "Player2"]
+//! [Date "2026-10-18"]
+//! [Result "1-0"]
+//!
+//! 1. b2 a1 2. c3 a3 3. a2 c2 4. c1 b1 5. a3 1-0
+//! ```
+//!
+//! Squares are a column letter followed by a row number counted from the bottom left (`a1`),
+//! with columns past `z` continuing as `aa`, `ab`, …. A super game has a `Size` like `3` and moves
+//! such as `B2:c3`, the board in upper case and the square within it in lower case. The result is
+//! `1-0` when X wins, `0-1` when O wins, `1/2-1/2` for a draw and `*` for an unfinished game.
+//! Games in which O moved first carry a `[First "O"]` tag, and normal games played by other rules a
+//! tag such as `[Rules "gravity"]`. Within tag values `\`, `"` and line breaks are escaped with a
+//! backslash, and boards are at most 32 squares a side like online.
+
+use std::fmt::{Display, Formatter};
+use crate::engine::{Board, MoveError, Outcome, Position, Rules, Square, SuperBoard, SuperMove};
+use crate::net::protocol::MAX_SIZE;
+
+/// Longest line of moves the writer produces before wrapping.
+const LINE_WIDTH: usize = 80;
+const TAGS: [&str; 9] = ["Mode", "Size", "WinLength", "Rules", "First", "X", "O", "Date", "Result"];
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Record {
+    pub x: String,
+    pub o: String,
+    pub date: String,
+    pub first: Square,
+    pub result: Outcome,
+    pub moves: RecordedMoves,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum RecordedMoves {
+    Normal { width: usize, height: usize, win_length: usize, rules: Rules, moves: Vec<Position> },
+    Super { size: usize, moves: Vec<SuperMove> },
+}
+
+/// Where and why a record could not be read. Lines and columns count from 1.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Record {
+    pub fn normal(board: &Board, x: &str, o: &str, date: &str) -> Self {
+        Record {
+            x: x.to_string(),
+            o: o.to_string(),
+            date: date.to_string(),
+            first: board.first(),
+            result: board.outcome(),
+            moves: RecordedMoves::Normal {
+                width: board.width(),
+                height: board.height(),
+                win_length: board.win_length(),
+                rules: board.rules(),
+                moves: board.moves().to_vec(),
+            },
+        }
+    }
+
+    pub fn super_game(board: &SuperBoard, x: &str, o: &str, date: &str) -> Self {
+        Record {
+            x: x.to_string(),
+            o: o.to_string(),
+            date: date.to_string(),
+            first: board.first(),
+            result: board.outcome(),
+            moves: RecordedMoves::Super { size: board.size(), moves: board.moves().to_vec() },
+        }
+    }
+
+    /// The moves as written in the record, such as `b2` or `B2:c3`.
+    pub fn move_names(&self) -> Vec<String> {
+        match &self.moves {
+            RecordedMoves::Normal { moves, .. } => moves.iter().map(|&mv| square_name(mv)).collect(),
+            RecordedMoves::Super { moves, .. } => moves.iter().map(|&mv| super_move_name(mv)).collect(),
+        }
+    }
+
+    /// The result as written in the record, such as `1-0` or `*`.
+    pub fn result_name(&self) -> &'static str {
+        result_name(&self.result)
+    }
+
+    pub fn write(&self) -> String {
+        let (mode, size, win_length, rules) = match &self.moves {
+            RecordedMoves::Normal { width, height, win_length, rules, .. } => ("normal", format!("{width}x{height}"), Some(win_length), rules.names()),
+            RecordedMoves::Super { size, .. } => ("super", size.to_string(), None, Vec::new()),
+        };
+        let mut text = format!("[Mode \"{mode}\"]\n[Size \"{size}\"]\n");
+        if let Some(win_length) = win_length {
+            text += &format!("[WinLength \"{win_length}\"]\n");
+        }
+        if !rules.is_empty() {
+            text += &format!("[Rules \"{}\"]\n", rules.join(" "));
+        }
+        if self.first == Square::Circle {
+            text += "[First \"O\"]\n";
+        }
+        text += &format!(
+            "[X \"{}\"]\n[O \"{}\"]\n[Date \"{}\"]\n[Result \"{}\"]\n\n",
+            escape(&self.x), escape(&self.o), escape(&self.date), result_name(&self.result)
+        );
+
+        let mut tokens = Vec::new();
+        for (index, mv) in self.move_names().into_iter().enumerate() {
+            if index % 2 == 0 {
+                tokens.push(format!("{}.", index / 2 + 1));
+            }
+            tokens.push(mv);
+        }
+        tokens.push(result_name(&self.result).to_string());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                text += &line;
+                text.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        text += &line;
+        text.push('\n');
+        text
+    }
+
+    /// Reads a record, checking that every move is legal and that the result matches the moves.
+    pub fn parse(text: &str) -> Result<Record, ParseError> {
+        Parser::new(text).record()
+    }
+}
+
+fn result_name(outcome: &Outcome) -> &'static str {
+    match outcome.winner() {
+        Square::X => "1-0",
+        Square::Circle => "0-1",
+        Square::Draw => "1/2-1/2",
+        Square::None => "*",
+        Square::Triangle | Square::Block => unreachable!("records are only written for two players"),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn column_name(mut column: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'a' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+fn square_name((x, y): Position) -> String {
+    format!("{}{}", column_name(x), y + 1)
+}
+
+fn super_move_name((board, square): SuperMove) -> String {
+    format!("{}:{}", square_name(board).to_uppercase(), square_name(square))
+}
+
+/// The column, counted in characters from 1, of the byte at `offset` in `line`.
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+/// A piece of the input together with the column it starts at.
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+struct Parser<'a> {
+    lines: Vec<&'a str>,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser { lines: text.lines().collect(), line: 0 }
+    }
+
+    fn error(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError { line, column, message: message.into() }
+    }
+
+    fn record(&mut self) -> Result<Record, ParseError> {
+        let mut headers: Vec<(&str, String, Token)> = Vec::new();
+        while let Some(line) = self.lines.get(self.line) {
+            let trimmed = line.trim();
+            if !trimmed.starts_with('[') {
+                if trimmed.is_empty() && headers.is_empty() {
+                    self.line += 1;
+                    continue;
+                }
+                break;
+            }
+            let (name, value, token) = self.header(line)?;
+            if headers.iter().any(|(other, _, _)| *other == name) {
+                return Err(Self::error(token.line, token.column, format!("duplicate tag {name}")));
+            }
+            headers.push((name, value, token));
+            self.line += 1;
+        }
+        let end_of_headers = self.line + 1;
+        if let Some((name, _, token)) = headers.iter().find(|(name, _, _)| !TAGS.contains(name)) {
+            return Err(Self::error(token.line, token.column - name.chars().count() - 1, format!("unknown tag {name}")));
+        }
+
+        let mut take = |name: &str| {
+            headers.iter().position(|(tag, _, _)| *tag == name).map(|index| headers.remove(index))
+        };
+        let mode = take("Mode").ok_or_else(|| Self::error(end_of_headers, 1, "missing Mode tag"))?;
+        let size = take("Size").ok_or_else(|| Self::error(end_of_headers, 1, "missing Size tag"))?;
+        let win_length = take("WinLength");
+        let rule_names = take("Rules");
+        let first = match take("First") {
+            None => Square::X,
+            Some((_, value, _)) if value == "X" => Square::X,
+            Some((_, value, _)) if value == "O" => Square::Circle,
+            Some((_, value, token)) => return Err(Self::error(token.line, token.column, format!("First must be X or O, not \"{value}\""))),
+        };
+        let x = take("X").map_or_else(String::new, |(_, value, _)| value);
+        let o = take("O").map_or_else(String::new, |(_, value, _)| value);
+        let date = take("Date").map_or_else(String::new, |(_, value, _)| value);
+        let result = take("Result").ok_or_else(|| Self::error(end_of_headers, 1, "missing Result tag"))?;
+
+        let mut game = match mode.1.as_str() {
+            "normal" => {
+                let (width, height) = size.1.split_once('x')
+                    .and_then(|(width, height)| Some((width.parse::<usize>().ok()?, height.parse::<usize>().ok()?)))
+                    .filter(|&(width, height)| (3..=MAX_SIZE).contains(&width) && (3..=MAX_SIZE).contains(&height))
+                    .ok_or_else(|| Self::error(size.2.line, size.2.column, format!("invalid size \"{}\", sides run from 3 to {MAX_SIZE}", size.1)))?;
+                let (_, value, token) = win_length.ok_or_else(|| Self::error(end_of_headers, 1, "missing WinLength tag"))?;
+                let win_length = value.parse::<usize>().ok()
+                    .filter(|win_length| (3..=width.max(height)).contains(win_length))
+                    .ok_or_else(|| Self::error(token.line, token.column, format!("invalid win length \"{value}\"")))?;
+                let mut rules = Rules::default();
+                if let Some((_, value, token)) = rule_names {
+                    if let Some(unknown) = value.split_whitespace().find(|&name| !rules.enable(name)) {
+                        return Err(Self::error(token.line, token.column, format!("unknown rule \"{unknown}\"")));
+                    }
+                    if rules.wild {
+                        return Err(Self::error(token.line, token.column, "wild games cannot be recorded, the moves do not say which mark was placed"));
+                    }
+                }
+                let mut board = Board::new(width, height, win_length);
+                board.set_turn(first);
+                board.set_rules(rules);
+                Replay::Normal(board)
+            }
+            "super" => {
+                if let Some((_, _, token)) = win_length {
+                    return Err(Self::error(token.line, token.column, "super games have no WinLength tag"));
+                }
+                if let Some((_, _, token)) = rule_names {
+                    return Err(Self::error(token.line, token.column, "super games have no Rules tag"));
+                }
+                let size = size.1.parse::<usize>().ok()
+                    .filter(|size| (3..=MAX_SIZE).contains(size))
+                    .ok_or_else(|| Self::error(size.2.line, size.2.column, format!("invalid size \"{}\", sizes run from 3 to {MAX_SIZE}", size.1)))?;
+                let mut board = SuperBoard::new(size);
+                board.set_first(first);
+                Replay::Super(board)
+            }
+            other => return Err(Self::error(mode.2.line, mode.2.column, format!("unknown mode \"{other}\""))),
+        };
+        let expected_result = match result.1.as_str() {
+            "1-0" | "0-1" | "1/2-1/2" | "*" => result.1,
+            other => return Err(Self::error(result.2.line, result.2.column, format!("unknown result \"{other}\""))),
+        };
+
+        let mut closing: Option<Token> = None;
+        let mut played = 0;
+        for token in self.move_tokens() {
+            if closing.is_some() {
+                return Err(Self::error(token.line, token.column, "moves after the result"));
+            }
+            if matches!(token.text, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                closing = Some(token);
+            } else if let Some(number) = token.text.strip_suffix('.') {
+                if played % 2 != 0 || number.parse() != Ok(played / 2 + 1) {
+                    return Err(Self::error(token.line, token.column, format!("expected move {}", played / 2 + 1)));
+                }
+            } else {
+                game.play(&token)?;
+                played += 1;
+            }
+        }
+        let closing = closing.ok_or_else(|| {
+            Self::error(self.lines.len().max(1), self.lines.last().map_or(1, |line| line.chars().count() + 1), "missing result")
+        })?;
+        if closing.text != expected_result {
+            return Err(Self::error(closing.line, closing.column, format!("result {} does not match the Result tag {expected_result}", closing.text)));
+        }
+        let result = game.outcome();
+        if result_name(&result) != expected_result {
+            return Err(Self::error(closing.line, closing.column, format!("the moves end in {}, not {expected_result}", result_name(&result))));
+        }
+
+        let moves = match game {
+            Replay::Normal(board) => RecordedMoves::Normal {
+                width: board.width(),
+                height: board.height(),
+                win_length: board.win_length(),
+                rules: board.rules(),
+                moves: board.moves().to_vec(),
+            },
+            Replay::Super(board) => RecordedMoves::Super { size: board.size(), moves: board.moves().to_vec() },
+        };
+        Ok(Record { x, o, date, first, result, moves })
+    }
+
+    fn header(&self, line: &'a str) -> Result<(&'a str, String, Token<'a>), ParseError> {
+        let number = self.line + 1;
+        let start = line.len() - line.trim_start().len();
+        let inner = line.trim().strip_prefix('[').unwrap();
+        let inner = inner.strip_suffix(']').ok_or_else(|| Self::error(number, column(line, line.trim_end().len()), "expected ]"))?;
+        let name_end = inner.find(' ').ok_or_else(|| Self::error(number, column(line, start + 1), "expected a tag name and value"))?;
+        let name = &inner[..name_end];
+        let value_start = column(line, start + 1 + name_end + 1);
+        let quoted = inner[name_end + 1..].strip_prefix('"').and_then(|value| value.strip_suffix('"'))
+            .ok_or_else(|| Self::error(number, value_start, "expected a quoted value"))?;
+        let mut value = String::new();
+        let mut chars = quoted.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' => value.extend(chars.next().map(|escaped| match escaped {
+                    'n' => '\n',
+                    'r' => '\r',
+                    escaped => escaped,
+                })),
+                '"' => return Err(Self::error(number, value_start, "unescaped \" in value")),
+                char => value.push(char),
+            }
+        }
+        Ok((name, value, Token { text: quoted, line: number, column: value_start }))
+    }
+
+    fn move_tokens(&self) -> Vec<Token<'a>> {
+        let mut tokens = Vec::new();
+        for (index, line) in self.lines.iter().enumerate().skip(self.line) {
+            let mut rest = *line;
+            let mut offset = 0;
+            while let Some(start) = rest.find(|char: char| !char.is_whitespace()) {
+                let length = rest[start..].find(char::is_whitespace).unwrap_or(rest.len() - start);
+                let text = &rest[start..start + length];
+                // Move numbers may be written without a space, as in `1.b2`.
+                let split = text.find('.').map_or(length, |dot| dot + 1);
+                tokens.push(Token { text: &text[..split], line: index + 1, column: column(line, offset + start) });
+                if split < length {
+                    tokens.push(Token { text: &text[split..], line: index + 1, column: column(line, offset + start + split) });
+                }
+                offset += start + length;
+                rest = &rest[start + length..];
+            }
+        }
+        tokens
+    }
+}
+
+/// The board moves are checked against while parsing.
+enum Replay {
+    Normal(Board),
+    Super(SuperBoard),
+}
+
+impl Replay {
+    fn outcome(&self) -> Outcome {
+        match self {
+            Replay::Normal(board) => board.outcome(),
+            Replay::Super(board) => board.outcome(),
+        }
+    }
+
+    fn play(&mut self, token: &Token) -> Result<(), ParseError> {
+        let result = match self {
+            Replay::Normal(board) => {
+                let mv = parse_square(token.text, false)
+                    .ok_or_else(|| Parser::error(token.line, token.column, format!("expected a square, found \"{}\"", token.text)))?;
+                board.play(mv)
+            }
+            Replay::Super(board) => {
+                let mv = token.text.split_once(':')
+                    .and_then(|(outer, inner)| Some((parse_square(outer, true)?, parse_square(inner, false)?)))
+                    .ok_or_else(|| Parser::error(token.line, token.column, format!("expected a move like B2:c3, found \"{}\"", token.text)))?;
+                board.play(mv)
+            }
+        };
+        result.map(|_| ()).map_err(|error: MoveError| Parser::error(token.line, token.column, format!("illegal move {}: {error}", token.text)))
+    }
+}
+
+fn parse_square(text: &str, upper_case: bool) -> Option<Position> {
+    let digits = text.find(|char: char| char.is_ascii_digit())?;
+    let (letters, row) = text.split_at(digits);
+    if letters.is_empty() || !row.bytes().all(|byte| byte.is_ascii_digit()) || row.starts_with('0') {
+        return None;
+    }
+    let mut column = 0usize;
+    for byte in letters.bytes() {
+        let letter = if upper_case { byte.is_ascii_uppercase() } else { byte.is_ascii_lowercase() };
+        if !letter {
+            return None;
+        }
+        column = column.checked_mul(26)?.checked_add((byte.to_ascii_lowercase() - b'a') as usize + 1)?;
+    }
+    Some((column - 1, row.parse::<usize>().ok()? - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADERS: &str = "[Mode \"normal\"]\n[Size \"3x3\"]\n[WinLength \"3\"]\n[Result \"*\"]\n\n";
+
+    fn error(text: &str) -> (usize, usize) {
+        let error = Record::parse(text).unwrap_err();
+        (error.line, error.column)
+    }
+
+    #[test]
+    fn normal_game_round_trips() {
+        let mut board = Board::new(3, 3, 3);
+        for mv in [(1, 1), (0, 0), (2, 2), (0, 2), (0, 1), (2, 1), (1, 0), (1, 2), (2, 0)] {
+            board.play(mv).unwrap();
+        }
+        let record = Record::normal(&board, "Jörg \"J\" Ünal", "line\nbreak \\ here", "2026-10-18");
+        let text = record.write();
+        assert!(text.contains("[X \"Jörg \\\"J\\\" Ünal\"]\n[O \"line\\nbreak \\\\ here\"]"));
+        assert!(text.ends_with("5. c1 1/2-1/2\n"));
+        assert_eq!(Record::parse(&text), Ok(record));
+    }
+
+    #[test]
+    fn super_game_round_trips() {
+        let mut board = SuperBoard::new(4);
+        board.set_first(Square::Circle);
+        for _ in 0..30 {
+            let mv = board.legal_moves()[0];
+            board.play(mv).unwrap();
+        }
+        let record = Record::super_game(&board, "Ann", "Bob", "2026-10-18");
+        let text = record.write();
+        assert!(text.contains("[First \"O\"]"));
+        assert!(text.lines().all(|line| line.len() <= LINE_WIDTH));
+        assert_eq!(Record::parse(&text), Ok(record));
+    }
+
+    #[test]
+    fn rules_round_trip_but_wild_games_are_refused() {
+        let mut board = Board::new(7, 6, 4);
+        board.set_rules(Rules { gravity: true, misere: true, wild: false });
+        for mv in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2), (1, 2), (0, 3)] {
+            board.play(mv).unwrap();
+        }
+        let record = Record::normal(&board, "Ann", "Bob", "2026-10-18");
+        let text = record.write();
+        assert!(text.contains("[Rules \"gravity misere\"]"));
+        assert!(text.ends_with("4. a4 0-1\n"));
+        assert_eq!(Record::parse(&text), Ok(record));
+
+        let wild = "[Mode \"normal\"]\n[Size \"3x3\"]\n[WinLength \"3\"]\n[Rules \"wild\"]\n[Result \"*\"]\n\n*\n";
+        assert_eq!(error(wild), (4, 8));
+    }
+
+    #[test]
+    fn oversized_boards_are_refused() {
+        assert_eq!(error("[Mode \"normal\"]\n[Size \"100000x100000\"]\n[WinLength \"3\"]\n[Result \"*\"]\n\n*\n"), (2, 7));
+        assert_eq!(error("[Mode \"super\"]\n[Size \"33\"]\n[Result \"*\"]\n\n*\n"), (2, 7));
+    }
+
+    #[test]
+    fn errors_point_at_the_character() {
+        assert_eq!(error(&format!("[X \"Jörg\"\n{HEADERS}*\n")), (1, 10));
+        assert_eq!(error(&format!("[Ünknown \"x\"]\n{HEADERS}*\n")), (1, 2));
+        assert_eq!(error(&format!("{HEADERS}1. b2 a1 2. b2 *\n")), (6, 13));
+        assert_eq!(error(&format!("{HEADERS}1. b2 a1\n2.c3 ä1 *\n")), (7, 6));
+        assert_eq!(error(&format!("{HEADERS}1. b2 a1\n")), (6, 9));
+        assert_eq!(error(&format!("{HEADERS}1. b2 1-0\n")), (6, 7));
+    }
+}
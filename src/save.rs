@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use crate::ai::Difficulty;
+use crate::clock::{Clock, OnTimeout, TimeControl};
+use crate::engine::{Position, Rules, Square, SuperMove, MARKS};
+use crate::notation::{ParseError, Record};
+
+/// A game in progress as written to disk. Boards are stored as the moves leading to them and
+/// rebuilt by replaying those, which restores whose turn it is and where a super game continues.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SavedGame {
+    pub saved_at: u64,
+    /// Points of each player, in the order of [`MARKS`](crate::engine::MARKS).
+    pub scores: Vec<u32>,
+    /// Who moved first, X in saves from before O could start.
+    #[serde(default = "x_first")]
+    pub first: Square,
+    pub board: SavedBoard,
+    /// The clock of a timed game, missing when played without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<SavedClock>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SavedClock {
+    pub control: TimeControl,
+    /// Time left for each player, in the order of [`MARKS`].
+    pub left: Vec<Duration>,
+    pub on_timeout: OnTimeout,
+    /// The player who lost on time, which ended the game.
+    pub out_of_time: Option<Square>,
+}
+
+impl SavedClock {
+    pub fn new(clock: &Clock, on_timeout: OnTimeout, out_of_time: Option<Square>) -> Self {
+        SavedClock { control: clock.control(), left: MARKS.iter().map(|mark| clock.left(*mark)).collect(), on_timeout, out_of_time }
+    }
+
+    pub fn clock(&self) -> Clock {
+        Clock::resume(self.control, &self.left)
+    }
+}
+
+fn x_first() -> Square {
+    Square::X
+}
+
+fn two_players() -> usize {
+    2
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum SavedBoard {
+    Normal {
+        width: usize,
+        height: usize,
+        win_length: usize,
+        moves: Vec<Position>,
+        computer: Option<Difficulty>,
+        #[serde(default)]
+        rules: Rules,
+        #[serde(default = "two_players")]
+        players: usize,
+        /// The mark placed by each move, only kept for wild games where it can be either.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        marks: Vec<Square>,
+    },
+    Super {
+        size: usize,
+        moves: Vec<SuperMove>,
+        think_time: Option<Duration>,
+        /// The computer's iteration budget, if its search was limited to one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        iterations: Option<usize>,
+    },
+}
+
+impl SavedGame {
+    pub fn new(scores: Vec<u32>, first: Square, board: SavedBoard, clock: Option<SavedClock>) -> Self {
+        SavedGame { saved_at: now(), scores, first, board, clock }
+    }
+
+    /// Checks what replaying the moves does not: that there are two to four players, each with
+    /// a score, and that the player who moved first and any who lost on time are among them.
+    pub fn check(&self) -> Result<()> {
+        let players = self.board.players();
+        if !(2..=MARKS.len()).contains(&players) {
+            bail!("the save is for {players} players");
+        }
+        if self.scores.len() != players {
+            bail!("the save has {} scores for {players} players", self.scores.len());
+        }
+        let marks = &MARKS[..players];
+        if !marks.contains(&self.first) {
+            bail!("{:?} cannot move first among {players} players", self.first);
+        }
+        if let Some(mark) = self.clock.as_ref().and_then(|clock| clock.out_of_time).filter(|mark| !marks.contains(mark)) {
+            bail!("{mark:?} cannot run out of time among {players} players");
+        }
+        Ok(())
+    }
+
+    /// One line summary for the list of saved games.
+    pub fn describe(&self) -> String {
+        let (mode, opponent) = match &self.board {
+            SavedBoard::Normal { width, height, win_length, computer, rules, players, .. } =>
+                (format!("{} {width}×{height}, {win_length} in a row{}", rules.mode_name(), players_suffix(*players)), computer.map(|difficulty| difficulty.name())),
+            SavedBoard::Super { size, think_time, .. } =>
+                (format!("Super {size}×{size}"), think_time.map(|_| "Computer")),
+        };
+        let opponent = opponent.map_or(String::new(), |opponent| format!(" vs {opponent}"));
+        let scores: Vec<String> = self.scores.iter().map(u32::to_string).collect();
+        format!("{}  {mode}{opponent}  {}", format_timestamp(self.saved_at), scores.join(":"))
+    }
+}
+
+impl SavedBoard {
+    pub fn players(&self) -> usize {
+        match self {
+            SavedBoard::Normal { players, .. } => *players,
+            SavedBoard::Super { .. } => 2,
+        }
+    }
+}
+
+/// Added to the description of a normal board played by more than two.
+pub fn players_suffix(players: usize) -> String {
+    if players > 2 { format!(", {players} players") } else { String::new() }
+}
+
+/// Where saved games, records and the game history go, inside the XDG data directory on Linux.
+pub fn data_dir(name: &str) -> Result<PathBuf> {
+    // Tests finish games too, which must not end up in the player's statistics.
+    #[cfg(test)]
+    let data_dir = Some(std::env::temp_dir().join("tic-tac-toe-tests"));
+    #[cfg(not(test))]
+    let data_dir = dirs::data_dir();
+    let data_dir = data_dir.ok_or_else(|| eyre!("no data directory found"))?;
+    Ok(data_dir.join("tic-tac-toe").join(name))
+}
+
+/// Writes `game` to `path`, or to a new file when `path` is `None`, and returns where it ended up.
+pub fn save(game: &SavedGame, path: Option<&Path>) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let dir = data_dir("saves")?;
+            fs::create_dir_all(&dir)?;
+            new_file(&dir, game.saved_at, "json")
+        }
+    };
+    fs::write(&path, serde_json::to_string_pretty(game)?)?;
+    Ok(path)
+}
+
+/// A path in `dir` named after `timestamp` that is not taken yet, numbering files made within
+/// the same second like `1760745600-1.json`.
+fn new_file(dir: &Path, timestamp: u64, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{timestamp}.{extension}"));
+    let mut copy = 0;
+    while path.exists() {
+        copy += 1;
+        path = dir.join(format!("{timestamp}-{copy}.{extension}"));
+    }
+    path
+}
+
+pub fn load(path: &Path) -> Result<SavedGame> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// All readable saved games, most recent first.
+pub fn list() -> Result<Vec<(PathBuf, SavedGame)>> {
+    let dir = data_dir("saves")?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut games: Vec<(PathBuf, SavedGame)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .filter_map(|path| load(&path).ok().map(|game| (path, game)))
+        .collect();
+    games.sort_by_key(|(_, game)| std::cmp::Reverse(game.saved_at));
+    Ok(games)
+}
+
+pub fn delete(path: &Path) -> Result<()> {
+    Ok(fs::remove_file(path)?)
+}
+
+/// Writes `record` in the text notation to a new file among the exported records.
+pub fn export(record: &Record) -> Result<PathBuf> {
+    let text = record.write();
+    debug_assert_eq!(Record::parse(&text).as_ref(), Ok(record));
+    let dir = data_dir("records")?;
+    fs::create_dir_all(&dir)?;
+    let path = new_file(&dir, now(), "ttt");
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// All exported records, most recent first, together with why a record could not be read.
+pub fn records() -> Result<Vec<(PathBuf, std::result::Result<Record, ParseError>)>> {
+    let dir = data_dir("records")?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut records: Vec<(PathBuf, std::result::Result<Record, ParseError>)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ttt"))
+        .filter_map(|path| fs::read_to_string(&path).ok().map(|text| (path, Record::parse(&text))))
+        .collect();
+    records.sort_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(records)
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Today's date as `YYYY-MM-DD` in UTC.
+pub fn today() -> String {
+    format_timestamp(now())[..10].to_string()
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86_400, timestamp % 86_400);
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
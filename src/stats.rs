@@ -0,0 +1,238 @@
+//! Results of finished local games, appended to a history file from which the statistics for
+//! every player are worked out.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use crate::engine::{Rules, Square, MARKS};
+use crate::save;
+
+/// The kind of board a game was played on, results are counted separately for each.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Variant {
+    Normal {
+        width: usize,
+        height: usize,
+        win_length: usize,
+        #[serde(default)]
+        rules: Rules,
+        #[serde(default = "two_players")]
+        players: usize,
+    },
+    Super { size: usize },
+    Cube { size: usize },
+}
+
+fn two_players() -> usize {
+    2
+}
+
+impl Variant {
+    pub fn name(&self) -> String {
+        match self {
+            Variant::Normal { width, height, win_length, rules, players } =>
+                format!("{} {width}×{height}, {win_length} in a row{}", rules.mode_name(), save::players_suffix(*players)),
+            Variant::Super { size } => format!("Super {size}×{size}"),
+            Variant::Cube { size } => format!("Qubic {size}×{size}×{size}"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct GameResult {
+    pub played_at: u64,
+    pub variant: Variant,
+    pub x: String,
+    pub o: String,
+    /// The names of the third and fourth player in games of more than two.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub others: Vec<String>,
+    pub first: Square,
+    /// The winning mark or `Draw`.
+    pub winner: Square,
+    pub moves: usize,
+}
+
+impl GameResult {
+    pub fn new(variant: Variant, x: &str, o: &str, first: Square, winner: Square, moves: usize) -> Self {
+        GameResult { played_at: save::now(), variant, x: x.to_string(), o: o.to_string(), others: Vec::new(), first, winner, moves }
+    }
+
+    /// Everyone who played and their mark, in turn order.
+    fn players(&self) -> impl Iterator<Item = (&str, Square)> {
+        [&self.x, &self.o].into_iter().chain(&self.others).map(String::as_str).zip(MARKS)
+    }
+}
+
+/// Adds `result` to the end of the history.
+pub fn record(result: &GameResult) -> Result<()> {
+    let path = save::data_dir("history.jsonl")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(result)?)?;
+    Ok(())
+}
+
+/// Every recorded game, oldest first. Lines that cannot be read are left out.
+pub fn history() -> Result<Vec<GameResult>> {
+    let path = save::data_dir("history.jsonl")?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+#[derive(Default, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The share of games won in percent.
+    pub fn win_rate(&self) -> u32 {
+        (self.wins * 100).checked_div(self.games()).unwrap_or(0)
+    }
+
+    /// Counts a game that `winner` won, from the point of view of `mark`.
+    fn add(&mut self, mark: Square, winner: Square) {
+        if winner == mark {
+            self.wins += 1;
+        } else if winner == Square::Draw {
+            self.draws += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+}
+
+pub struct PlayerStats {
+    pub name: String,
+    pub total: Tally,
+    pub by_variant: BTreeMap<Variant, Tally>,
+    /// Wins in a row up to the latest game.
+    pub streak: u32,
+    pub best_streak: u32,
+    moves: usize,
+}
+
+impl PlayerStats {
+    fn new(name: &str) -> Self {
+        PlayerStats { name: name.to_string(), total: Tally::default(), by_variant: BTreeMap::new(), streak: 0, best_streak: 0, moves: 0 }
+    }
+
+    /// Moves per game on average.
+    pub fn average_length(&self) -> f64 {
+        if self.total.games() == 0 { 0.0 } else { self.moves as f64 / self.total.games() as f64 }
+    }
+
+    fn add(&mut self, result: &GameResult, mark: Square) {
+        self.total.add(mark, result.winner);
+        self.by_variant.entry(result.variant).or_default().add(mark, result.winner);
+        self.moves += result.moves;
+        if result.winner == mark {
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+}
+
+pub struct Summary {
+    /// Everyone who played, most wins first.
+    pub players: Vec<PlayerStats>,
+    /// How the player who moved first did on each kind of board.
+    pub first_player: BTreeMap<Variant, Tally>,
+}
+
+impl Summary {
+    /// Works out the statistics from `history`, which is oldest first.
+    pub fn new(history: &[GameResult]) -> Self {
+        let mut players: BTreeMap<&str, PlayerStats> = BTreeMap::new();
+        let mut first_player: BTreeMap<Variant, Tally> = BTreeMap::new();
+        for result in history {
+            let mut counted = Vec::new();
+            for (name, mark) in result.players() {
+                // Someone playing several sides is only counted once.
+                if !counted.contains(&name) {
+                    players.entry(name).or_insert_with(|| PlayerStats::new(name)).add(result, mark);
+                    counted.push(name);
+                }
+            }
+            first_player.entry(result.variant).or_default().add(result.first, result.winner);
+        }
+        let mut players: Vec<PlayerStats> = players.into_values().collect();
+        players.sort_by_key(|player| std::cmp::Reverse(player.total.wins));
+        Summary { players, first_player }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NORMAL: Variant = Variant::Normal { width: 3, height: 3, win_length: 3, rules: Rules { gravity: false, misere: false, wild: false }, players: 2 };
+
+    fn result(x: &str, o: &str, first: Square, winner: Square, moves: usize) -> GameResult {
+        GameResult::new(NORMAL, x, o, first, winner, moves)
+    }
+
+    #[test]
+    fn tallies_streaks_and_lengths_per_player() {
+        let history = [
+            result("Ann", "Bob", Square::X, Square::X, 5),
+            result("Ann", "Bob", Square::X, Square::X, 7),
+            result("Bob", "Ann", Square::X, Square::Draw, 9),
+            result("Ann", "Bob", Square::Circle, Square::X, 7),
+            result("Ann", "Computer", Square::X, Square::Circle, 6),
+        ];
+        let summary = Summary::new(&history);
+        let names: Vec<&str> = summary.players.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, ["Ann", "Computer", "Bob"]);
+
+        let ann = &summary.players[0];
+        assert_eq!(ann.total, Tally { wins: 3, losses: 1, draws: 1 });
+        assert_eq!(ann.by_variant[&NORMAL].games(), 5);
+        assert_eq!((ann.streak, ann.best_streak), (0, 2));
+        assert_eq!(ann.average_length(), 6.8);
+        assert_eq!(ann.total.win_rate(), 60);
+
+        let bob = &summary.players[2];
+        assert_eq!(bob.total, Tally { wins: 0, losses: 3, draws: 1 });
+    }
+
+    #[test]
+    fn first_player_advantage() {
+        let history = [
+            result("Ann", "Bob", Square::X, Square::X, 5),
+            result("Ann", "Bob", Square::Circle, Square::X, 6),
+            result("Ann", "Bob", Square::Circle, Square::Circle, 7),
+            result("Ann", "Bob", Square::X, Square::Draw, 9),
+        ];
+        let summary = Summary::new(&history);
+        assert_eq!(summary.first_player[&NORMAL], Tally { wins: 2, losses: 1, draws: 1 });
+    }
+
+    #[test]
+    fn every_player_of_a_bigger_game_is_counted() {
+        let mut game = GameResult::new(NORMAL, "Ann", "Bob", Square::X, Square::Triangle, 12);
+        game.others = vec!["Cat".to_string(), "Dan".to_string()];
+        let summary = Summary::new(&[game]);
+        let names: Vec<&str> = summary.players.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names[0], "Cat");
+        assert_eq!(names.len(), 4);
+        assert_eq!(summary.players[1].total, Tally { wins: 0, losses: 1, draws: 0 });
+    }
+}